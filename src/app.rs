@@ -0,0 +1,255 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    process::Command,
+};
+
+use anyhow::anyhow;
+use lazy_regex::{Lazy, regex};
+use regex::Regex;
+
+use crate::{log::LogEntry, prelude::*};
+
+const START_PROC_REGEX: &Lazy<Regex> = regex!(r"^Start proc (\d+):([^/\s]+)");
+const PROCESS_DIED_REGEX: &Lazy<Regex> = regex!(r"^Process (\S+) \(pid (\d+)\) has died");
+const AM_PROC_START_REGEX: &Lazy<Regex> = regex!(r"^\[\d+,(\d+),\d+,([^,\]]+)");
+const AM_PROC_DIED_REGEX: &Lazy<Regex> = regex!(r"^\[\d+,(\d+),([^,\]]+)");
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AppEvent {
+    Started { process: String, pid: u32 },
+    Died { process: String, pid: u32 },
+}
+
+/// Tracks the pids of an app's processes by watching process lifecycle lines in the log stream.
+///
+/// Pids of processes that died are still tracked, so lines logged after the death are not lost, unless the pid is
+/// reused by another process.
+pub struct AppTracker {
+    package: String,
+    pids: RefCell<HashSet<u32>>,
+}
+
+impl AppTracker {
+    pub fn new(package: &str) -> Self {
+        Self {
+            package: package.to_owned(),
+            pids: RefCell::new(HashSet::new()),
+        }
+    }
+
+    /// Seeds the tracked pids with the app's currently running processes, using `adb shell pidof`.
+    pub fn track_running_processes(&self) -> Result<()> {
+        let output = Command::new("adb")
+            .args(["shell", "pidof", &self.package])
+            .output()
+            .map_err(|e| anyhow!("failed to execute `adb shell pidof`: {e}"))?;
+
+        let pids = String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .map(str::parse::<u32>)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| anyhow!("unexpected output from `adb shell pidof`: {e}"))?;
+
+        self.pids.borrow_mut().extend(pids);
+        Ok(())
+    }
+
+    pub fn is_tracked(&self, pid: u32) -> bool {
+        self.pids.borrow().contains(&pid)
+    }
+
+    /// Updates the tracked pids according to `entry`, returning the app's lifecycle event it describes, if any.
+    pub fn observe(&self, entry: &LogEntry) -> Option<AppEvent> {
+        let (process, pid, started) = parse_lifecycle(entry)?;
+
+        if !self.is_app_process(&process) {
+            if started {
+                self.pids.borrow_mut().remove(&pid);
+            }
+            return None;
+        }
+
+        if started {
+            // The same start is often logged by both `ActivityManager` and `am_proc_start`.
+            self.pids
+                .borrow_mut()
+                .insert(pid)
+                .then_some(AppEvent::Started { process, pid })
+        } else {
+            self.pids
+                .borrow()
+                .contains(&pid)
+                .then_some(AppEvent::Died { process, pid })
+        }
+    }
+
+    /// Returns `true` for the app's main process and its named processes, e.g. `com.example.foo:remote`.
+    fn is_app_process(&self, process: &str) -> bool {
+        process
+            .strip_prefix(&self.package)
+            .is_some_and(|suffix| suffix.is_empty() || suffix.starts_with(':'))
+    }
+}
+
+/// Remembers the name of each process by watching process start lines in the log stream.
+pub struct ProcessNames {
+    names: RefCell<HashMap<u32, String>>,
+}
+
+impl ProcessNames {
+    pub fn new() -> Self {
+        Self {
+            names: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Seeds the names with the currently running processes, using `adb shell ps`.
+    pub fn track_running_processes(&self) -> Result<()> {
+        let output = Command::new("adb")
+            .args(["shell", "ps", "-A", "-o", "PID,NAME"])
+            .output()
+            .map_err(|e| anyhow!("failed to execute `adb shell ps`: {e}"))?;
+
+        let processes = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let (pid, name) = line.trim().split_once(char::is_whitespace)?;
+                Some((pid.parse().ok()?, name.trim().to_owned()))
+            })
+            .collect::<Vec<_>>();
+
+        self.names.borrow_mut().extend(processes);
+        Ok(())
+    }
+
+    pub fn observe(&self, entry: &LogEntry) {
+        if let Some((process, pid, true)) = parse_lifecycle(entry) {
+            self.names.borrow_mut().insert(pid, process);
+        }
+    }
+
+    pub fn get(&self, pid: u32) -> Option<String> {
+        self.names.borrow().get(&pid).cloned()
+    }
+}
+
+/// Returns the package of a process, e.g. `com.example.app` for `com.example.app:remote`.
+pub fn package(process: &str) -> &str {
+    process
+        .split_once(':')
+        .map_or(process, |(package, _)| package)
+}
+
+/// Returns the process, pid and whether the process started, for lines describing a process start or death.
+fn parse_lifecycle(entry: &LogEntry) -> Option<(String, u32, bool)> {
+    match entry.tag.as_str() {
+        "ActivityManager" => parse_captures(START_PROC_REGEX, &entry.message, 1, 2, true)
+            .or_else(|| parse_captures(PROCESS_DIED_REGEX, &entry.message, 2, 1, false)),
+        "am_proc_start" => parse_captures(AM_PROC_START_REGEX, &entry.message, 1, 2, true),
+        "am_proc_died" => parse_captures(AM_PROC_DIED_REGEX, &entry.message, 1, 2, false),
+        _ => None,
+    }
+}
+
+fn parse_captures(
+    regex: &Regex,
+    message: &str,
+    pid_group: usize,
+    process_group: usize,
+    started: bool,
+) -> Option<(String, u32, bool)> {
+    let captures = regex.captures(message)?;
+    let pid = captures[pid_group].parse().ok()?;
+    Some((captures[process_group].to_owned(), pid, started))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_track_app_across_restarts() {
+        let tracker = AppTracker::new("com.example.foo");
+
+        let started = tracker.observe(&LogEntry::test(
+            "ActivityManager",
+            "Start proc 4375:com.example.foo/u0a123 for activity {com.example.foo/.MainActivity}",
+        ));
+        assert_eq!(
+            started,
+            Some(AppEvent::Started {
+                process: "com.example.foo".to_owned(),
+                pid: 4375
+            })
+        );
+        assert!(tracker.is_tracked(4375));
+
+        let duplicate = tracker.observe(&LogEntry::test(
+            "am_proc_start",
+            "[0,4375,10123,com.example.foo,activity,{}]",
+        ));
+        assert_eq!(duplicate, None);
+
+        let died = tracker.observe(&LogEntry::test(
+            "ActivityManager",
+            "Process com.example.foo (pid 4375) has died: fg TOP",
+        ));
+        assert_eq!(
+            died,
+            Some(AppEvent::Died {
+                process: "com.example.foo".to_owned(),
+                pid: 4375
+            })
+        );
+        assert!(tracker.is_tracked(4375));
+
+        let restarted = tracker.observe(&LogEntry::test(
+            "am_proc_start",
+            "[0,4400,10123,com.example.foo:remote,service,{}]",
+        ));
+        assert!(restarted.is_some());
+        assert!(tracker.is_tracked(4400));
+    }
+
+    #[test]
+    fn test_ignore_other_processes() {
+        let tracker = AppTracker::new("com.example.foo");
+        tracker.observe(&LogEntry::test(
+            "am_proc_start",
+            "[0,4375,10123,com.example.foo,activity,{}]",
+        ));
+
+        let other = tracker.observe(&LogEntry::test(
+            "am_proc_start",
+            "[0,4375,10124,com.example.foobar,activity,{}]",
+        ));
+        assert_eq!(other, None);
+        assert!(!tracker.is_tracked(4375));
+
+        assert_eq!(
+            tracker.observe(&LogEntry::test("am_proc_died", "[0,4375,com.example.foobar,900,17]")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_remember_process_names() {
+        let names = ProcessNames::new();
+        names.observe(&LogEntry::test(
+            "ActivityManager",
+            "Start proc 4375:com.example.foo/u0a123 for activity {com.example.foo/.MainActivity}",
+        ));
+        names.observe(&LogEntry::test(
+            "am_proc_start",
+            "[0,4400,10123,com.example.foo:remote,service,{}]",
+        ));
+        names.observe(&LogEntry::test("am_proc_died", "[0,4375,com.example.foo,900,17]"));
+
+        assert_eq!(names.get(4375).as_deref(), Some("com.example.foo"));
+        assert_eq!(names.get(4400).as_deref(), Some("com.example.foo:remote"));
+        assert_eq!(names.get(1000), None);
+        assert_eq!(package("com.example.foo:remote"), "com.example.foo");
+    }
+}
@@ -1,6 +1,16 @@
-use clap::{Parser, ValueHint};
+use clap::{Parser, Subcommand, ValueHint};
 
-use crate::log::LogLevel;
+use crate::{
+    filter::TagMatch,
+    format::{ColorBy, ColorDepth, ColorMode, DeltaBy, TagAlign, TagWidth, TimeMode, WrapMode},
+    log::LogLevel,
+    output::{OutputFormat, Rotation, SplitBy, SplitFormat},
+    parse::InputFormat,
+    stats::StatsFormat,
+    symbolize::DEFAULT_SYMBOLIZER,
+    timeline::{ChartStyle, parse_bucket},
+    zone::Zone,
+};
 
 #[derive(Debug, Parser)]
 #[command(version, about, long_about=None)]
@@ -12,8 +22,52 @@ pub struct Args {
     #[clap(flatten)]
     pub input: Input,
 
+    #[clap(flatten)]
+    pub parsing: Parsing,
+
     #[clap(flatten)]
     pub filter: Filter,
+
+    #[clap(flatten)]
+    pub matching: Matching,
+
+    #[clap(flatten)]
+    pub format: Format,
+
+    #[clap(flatten)]
+    pub saving: Saving,
+
+    #[clap(flatten)]
+    pub statistics: Statistics,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Chart the volume of entries over time instead of printing them (see more with '--help')
+    ///
+    /// Entries are counted after filtering, in buckets of time, and charted once the input ends: as a row per bucket
+    /// with a bar stacked by level, or as a sparkline. Options of the input and filters go before `timeline`, e.g.
+    /// `nyancat --file capture.txt --level W timeline --bucket 5m`.
+    #[command(verbatim_doc_comment)]
+    Timeline(TimelineOptions),
+}
+
+#[derive(Debug, Parser)]
+pub struct TimelineOptions {
+    /// How the volume is charted: `bars`, a row per bucket with a bar stacked by level, or `sparkline`.
+    #[arg(long, value_name("bars|sparkline"), default_value_t = ChartStyle::Bars)]
+    pub style: ChartStyle,
+
+    /// The time each bucket spans, in `s`, `m`, `h` or `d`, e.g. `5m` [default: fitted to the terminal].
+    #[arg(long, value_name("DURATION"), value_parser = parse_bucket)]
+    pub bucket: Option<i64>,
+
+    /// Break the volume down by the N tags with the most entries.
+    #[arg(long, value_name("N"), default_value_t = 0)]
+    pub top_tags: usize,
 }
 
 #[derive(Debug, Parser)]
@@ -28,6 +82,31 @@ pub struct Input {
     pub exec_adb_logcat: bool,
 }
 
+#[derive(Debug, Parser)]
+#[group(required(false), multiple(true))]
+pub struct Parsing {
+    /// The format of the input (see more with '--help')
+    ///
+    /// - `auto` reads JSON Lines if the first line is a JSON object, and logcat output otherwise.
+    /// - `logcat` reads the output of `adb logcat`.
+    /// - `jsonl` reads the JSON Lines written by `--output=jsonl`. Fields other than those of the schema are ignored.
+    /// - `csv` reads CSV with a header row, see `--csv-columns`, which implies it.
+    #[arg(long, value_name("auto|logcat|jsonl|csv"), default_value_t = InputFormat::Auto, verbatim_doc_comment)]
+    pub input_format: InputFormat,
+
+    /// Which CSV columns the fields of entries are read from, implying `--input-format csv` (see more with '--help')
+    ///
+    /// Fields are `datetime`, `pid`, `tid`, `level`, `tag` and `message`. Unmapped fields are read from the column of
+    /// the same name, or `time`, `timestamp` or `date` for `datetime`, `priority` for `level` and `msg` for
+    /// `message`, ignoring case. Only `datetime` and `message` are required.
+    /// Datetimes can be written in RFC 3339, as `2025-10-01 12:10:36.746`, like logcat or as Unix timestamps in
+    /// seconds or milliseconds. Levels can be written like logcat (`I`), by name (`info`) or as priorities (`4`).
+    /// For example:
+    /// - Passing `datetime=logged_at,message=text` reads times from `logged_at` and messages from `text`.
+    #[arg(long, value_delimiter(','), value_name("FIELD=COLUMN[,...]"), verbatim_doc_comment)]
+    pub csv_columns: Option<Vec<String>>,
+}
+
 #[derive(Debug, Parser)]
 #[group(required(false), multiple(true))]
 pub struct Filter {
@@ -35,6 +114,15 @@ pub struct Filter {
     #[arg(long)]
     pub pid: Option<u32>,
 
+    /// Only include log lines logged by the app with this package name, following it across restarts (see more with
+    /// '--help')
+    ///
+    /// The app's processes are tracked by watching the `ActivityManager` and `am_proc_start`/`am_proc_died` lines in
+    /// the input, and when executing `adb logcat`, also by running `adb shell pidof` at startup.
+    /// A banner line is printed whenever one of the app's processes starts or dies.
+    #[arg(long, value_name("PACKAGE"), verbatim_doc_comment)]
+    pub app: Option<String>,
+
     /// Only include log lines logged from a thread with this tid.
     #[arg(long)]
     pub tid: Option<u32>,
@@ -43,6 +131,58 @@ pub struct Filter {
     #[arg(short('L'), long, value_name("V|D|I|W|E"))]
     pub level: Option<LogLevel>,
 
+    /// Only include log lines logged at or after TIME (see more with '--help')
+    ///
+    /// TIME is written like logcat, e.g. `10-01 12:10` or `10-01 12:10:36.746` in the current year, with a year, e.g.
+    /// `2025-10-01 12:10`, or in RFC 3339, e.g. `2025-10-01T12:10:36Z`. Times without an offset are on the clocks of
+    /// `--display-tz`, like the times that are printed.
+    #[arg(long, value_name("TIME"), verbatim_doc_comment)]
+    pub since: Option<String>,
+
+    /// Only include log lines logged before TIME, written like for `--since`.
+    #[arg(long, value_name("TIME"))]
+    pub until: Option<String>,
+
+    /// Only include crashes, ANRs and force finished activities, each with the lines its process logged shortly before.
+    ///
+    /// The other filters are applied first, so lines they exclude are never treated as part of a crash. A crash is
+    /// printed once its process logs something else, another process logs, or the input is quiet for a second.
+    #[arg(long, default_value_t = false)]
+    pub crashes: bool,
+
+    /// How many seconds of lines logged before a crash to include with it.
+    #[arg(long, value_name("SECONDS"), default_value_t = 5.0, requires("crashes"))]
+    pub crash_context: f64,
+
+    /// Limit how many lines each tag may log, dropping the rest (see more with '--help')
+    ///
+    /// Can be repeated by passing this argument multiple times.
+    ///
+    /// The tag is matched as a regex against the entire tag of each line, like with `--tag`, and each matching tag is
+    /// limited separately. The rate is given per second (`N/s`) or per minute (`N/m`).
+    /// Whenever lines are dropped, the number of dropped lines is printed before the next line of the tag.
+    /// For example:
+    /// - Passing `FileUtils=10/s` will print at most 10 lines of `FileUtils` per second.
+    /// - Passing `Finsky.*=100/m` will print at most 100 lines per minute of each tag starting with `Finsky`.
+    #[arg(long, value_name("TAG=N/s"), verbatim_doc_comment)]
+    pub rate_limit: Option<Vec<String>>,
+
+    /// Only include log lines whose message has structured fields meeting the specified condition (see more with
+    /// '--help')
+    ///
+    /// Can be repeated by passing this argument multiple times, in which case all conditions must be met.
+    ///
+    /// Messages are searched for `key=value` pairs, referenced as `field.<key>`, and for embedded JSON objects, whose
+    /// values are referenced as `json.<path>`. Values are compared as numbers when compared with a number.
+    /// The supported operators are `==`, `!=`, `<`, `<=`, `>`, `>=` and `~` (regex match, following `-i`, `-F` and
+    /// `--smart-case`), and without an operator the field only has to exist.
+    /// For example:
+    /// - Passing `field.tclass == "file"` will match `avc: denied { read } for ... tclass=file`.
+    /// - Passing `json.status >= 500` will match `request failed: {"status": 503}`.
+    /// - Passing `json.error.code` will match any message with a JSON object containing `{"error": {"code": ...}}`.
+    #[arg(short('W'), long("where"), value_name("CONDITION"), verbatim_doc_comment)]
+    pub field: Option<Vec<String>>,
+
     /// Only include log lines with one of the specified tags (see more with '--help')
     ///
     /// Each specified tag is matched as a regex against the tag of each line, and if one of the tags matches, the line
@@ -53,6 +193,8 @@ pub struct Filter {
     /// - Passing `Tag` will only match `Tag`.
     /// - Passing `Tag\d` will match `Tag1`, `Tag2`, etc.
     /// - Passing `Tag.*` will match anything starting with `Tag`, e.g. `Tag`, `TagABC`, `Tag123`...
+    ///
+    /// Tags can also be matched exactly, as globs or as substrings with `--tag-match`.
     #[arg(
         short('T'),
         long,
@@ -77,3 +219,320 @@ pub struct Filter {
     #[arg(short('M'), long, value_name("REGEX"), verbatim_doc_comment)]
     pub message: Option<Vec<String>>,
 }
+
+#[derive(Debug, Parser)]
+#[group(required(false), multiple(true))]
+pub struct Matching {
+    /// How the tags passed to `--tag` are matched against the tag of each line (see more with '--help')
+    ///
+    /// - `exact`: the tag must be equal to the passed tag.
+    /// - `glob`: the tag must match the passed glob, where `*` matches anything and `?` matches a single character.
+    /// - `regex`: the tag must match the passed regex in its entirety.
+    /// - `substring`: the tag must contain the passed tag.
+    #[arg(
+        long,
+        value_name("exact|glob|regex|substring"),
+        default_value_t = TagMatch::Regex,
+        verbatim_doc_comment
+    )]
+    pub tag_match: TagMatch,
+
+    /// Match the patterns of all filters case insensitively.
+    #[arg(short('i'), long, default_value_t = false, conflicts_with("smart_case"))]
+    pub ignore_case: bool,
+
+    /// Match the patterns of all filters case insensitively, unless they contain an uppercase character.
+    #[arg(long, default_value_t = false)]
+    pub smart_case: bool,
+
+    /// Treat the patterns of all filters as literal strings rather than regexes.
+    #[arg(short('F'), long, default_value_t = false)]
+    pub fixed_strings: bool,
+}
+
+#[derive(Debug, Parser)]
+#[group(required(false), multiple(true))]
+pub struct Format {
+    /// When to color the output (see more with '--help')
+    ///
+    /// With `auto`, the output is colored if stdout is a terminal. Setting the `NO_COLOR` environment variable disables
+    /// colors, and setting `CLICOLOR_FORCE` enables them even when piping, e.g. into `less -R`.
+    #[arg(long, value_name("auto|always|never"), default_value_t = ColorMode::Auto, verbatim_doc_comment)]
+    pub color: ColorMode,
+
+    /// How to write the log lines (see more with '--help')
+    ///
+    /// - `text` formats them for reading, colored according to `--color`.
+    /// - `plain` formats them like `text`, without colors, e.g. to diff two runs.
+    /// - `logcat` writes them in the `threadtime` format of `adb logcat`, after filtering and deobfuscation, so that
+    ///   they can be read by other logcat tools. Times are written in the `--display-tz` time zone.
+    /// - `jsonl` writes each header and entry as a JSON object on its own line, without colors, crash banners or
+    ///   collapsed frames. Entries have the fields `type` (`entry`), `datetime` (RFC 3339), `pid`, `tid`, `level`
+    ///   (e.g. `info`), `tag` and `message`, and headers have the fields `type` (`header`) and `buffer`. Both have
+    ///   `source`, and `process` and `device` when they are known. Fields are never renamed or removed.
+    /// - `html` writes a standalone HTML page laid out like `text`, always colored with the theme. Levels can be
+    ///   toggled, the lines filtered by their text, and stack traces collapsed.
+    #[arg(long, value_name("text|plain|logcat|jsonl|html"), default_value_t = OutputFormat::Text, verbatim_doc_comment)]
+    pub output: OutputFormat,
+
+    /// The number of colors the terminal can display (see more with '--help')
+    ///
+    /// Detected from the `COLORTERM` and `TERM` environment variables by default. Colors of themes and highlights the
+    /// terminal can't display are replaced by the closest ones it can.
+    #[arg(long, value_name("16|256|truecolor"), verbatim_doc_comment)]
+    pub color_depth: Option<ColorDepth>,
+
+    /// Lay out log lines with a template or one of logcat's formats (see more with '--help')
+    ///
+    /// Fields are written in braces: `{time}`, `{level}`, `{tag}`, `{pid}`, `{tid}`, `{msg}`, `{process}` (the name of
+    /// the process, when known), `{device}`, `{source}` (the input file or command) and `{delta}` (the time since the
+    /// previous line). A field can be followed by a colon and a width, optionally aligned with `<`, `>` or `^`, or by a
+    /// `strftime`-like format for `{time}`. `{tag:auto}` follows `--tag-width` and `--tag-align`.
+    ///
+    /// The available formats are `threadtime` (the default), `brief`, `tag`, `process`, `thread`, `time` and `raw`.
+    /// For example:
+    /// - Passing `brief` will print lines like `I/Finsky(14344): message`.
+    /// - Passing `{time:%H:%M:%S%.3f} {level} {tag:20} {pid}/{tid} {msg}` will print lines like
+    ///   `12:10:36.746 I Finsky               14344/14344 message`.
+    #[arg(long("format"), value_name("FORMAT|TEMPLATE"), verbatim_doc_comment)]
+    pub template: Option<String>,
+
+    /// What the time of log lines shows (see more with '--help')
+    ///
+    /// - `absolute` shows when the line was logged.
+    /// - `relative` shows the seconds since the first printed line.
+    /// - `delta` shows the seconds since the previous printed line.
+    /// - `elapsed` shows both the seconds since the first and since the previous printed line.
+    ///
+    /// Times since the previous line longer than `--slow-gap` are colored differently to make stalls visible.
+    #[arg(
+        long,
+        value_name("absolute|relative|delta|elapsed"),
+        default_value_t = TimeMode::Absolute,
+        verbatim_doc_comment
+    )]
+    pub time: TimeMode,
+
+    /// Measure the time since the previous line from the previous line of the same process or thread.
+    #[arg(long, value_name("pid|tid"))]
+    pub delta_by: Option<DeltaBy>,
+
+    /// How many seconds since the previous line are colored as a stall.
+    #[arg(long, value_name("SECONDS"), default_value_t = 1.0)]
+    pub slow_gap: f64,
+
+    /// Print a marker line between consecutive lines logged more than this many seconds apart.
+    #[arg(long, value_name("SECONDS"))]
+    pub gap_marker: Option<f64>,
+
+    /// The time zone of the times in the input (see more with '--help')
+    ///
+    /// Logcat prints times on the clock of the device without their zone, unless its `zone` modifier is used, in which
+    /// case the printed offsets take precedence. Defaults to the `persist.sys.timezone` property when reading a
+    /// bugreport, and to the local time zone otherwise.
+    #[arg(long, value_name("local|utc|OFFSET|ZONE"), verbatim_doc_comment)]
+    pub source_tz: Option<Zone>,
+
+    /// The time zone to display times in, the local time zone by default (see more with '--help')
+    ///
+    /// For example:
+    /// - Passing `utc` will display times in UTC.
+    /// - Passing `Europe/Berlin` will display times in Berlin, following its daylight saving time changes.
+    /// - Passing `-07:00` will display times 7 hours behind UTC.
+    #[arg(long, value_name("local|utc|OFFSET|ZONE"), verbatim_doc_comment)]
+    pub display_tz: Option<Zone>,
+
+    /// Color the output with a built-in theme or a TOML theme file (see more with '--help')
+    ///
+    /// Without this argument, `~/.config/nyancat/theme.toml` is used if it exists, and the dark theme otherwise.
+    /// A theme file can style every element of the output, and is overlaid on the built-in theme named by its `base`
+    /// key (dark by default).
+    /// For example:
+    /// - Passing `light` will use colors readable on light terminals.
+    /// - Passing `~/themes/mine.toml` containing `pid = { foreground = "blue", bold = true }` will use the dark theme
+    ///   with bold blue pids.
+    #[arg(long, value_name("dark|light|solarized|high-contrast|FILE"), verbatim_doc_comment)]
+    pub theme: Option<String>,
+
+    /// Highlight parts of log lines matching the specified regex (see more with '--help')
+    ///
+    /// Can be repeated by passing this argument multiple times. Unlike the filters, highlighting never hides lines.
+    ///
+    /// Every match of the regex in a message is highlighted, and the tag or pid of a line is highlighted if the regex
+    /// matches it entirely. A color can be chosen by appending `=COLOR` to the regex.
+    /// For example:
+    /// - Passing `uid = \d+` will highlight `uid = 10147` in yellow.
+    /// - Passing `Finsky=red` will highlight `Finsky` in red, both in messages and as a tag.
+    /// - Passing `#[0-9a-f]{6}=#ff8800` will highlight hex colors in orange.
+    #[arg(short('H'), long, value_name("REGEX[=COLOR]"), verbatim_doc_comment)]
+    pub highlight: Option<Vec<String>>,
+
+    /// Give each distinct tag, pid or tid its own color (see more with '--help')
+    ///
+    /// Colors are derived from a hash of the value, so a tag always gets the same color across runs and machines. The
+    /// palette depends on the number of colors the terminal supports. Specific tags can be pinned to a color with the
+    /// `[tag_colors]` table of the theme file.
+    /// For example:
+    /// - Passing `tag` will color each tag differently.
+    /// - Passing `tag,pid` will also color each pid differently.
+    /// - A theme file containing `tag_colors = { Finsky = "red" }` will always color `Finsky` in red.
+    #[arg(long, value_delimiter(','), value_name("tag|pid|tid[,...]"), verbatim_doc_comment)]
+    pub color_by: Option<Vec<ColorBy>>,
+
+    /// The width of the tag column (see more with '--help')
+    ///
+    /// With `auto`, the column grows to fit the longest tag seen so far, up to 23 characters. Tags longer than the
+    /// column are truncated with an ellipsis.
+    #[arg(long, value_name("N|auto"), default_value("auto"), verbatim_doc_comment)]
+    pub tag_width: TagWidth,
+
+    /// How tags are aligned in the tag column.
+    #[arg(long, value_name("left|right|center"), default_value_t = TagAlign::Left)]
+    pub tag_align: TagAlign,
+
+    /// How to print lines wider than the terminal (see more with '--help')
+    ///
+    /// Lines are only wrapped or truncated when printing to a terminal, and follow its width when it's resized.
+    /// - `indent` wraps messages at word boundaries, continuing them under the message column.
+    /// - `terminal` wraps messages at word boundaries, continuing them at the start of the next line.
+    /// - `none` leaves long lines to the terminal.
+    /// - `truncate` cuts long lines at the edge of the terminal.
+    #[arg(
+        long,
+        value_name("indent|terminal|none|truncate"),
+        default_value_t = WrapMode::Indent,
+        verbatim_doc_comment
+    )]
+    pub wrap: WrapMode,
+
+    /// Collapse runs of at least this many framework frames in stack traces, or 0 to print every frame (see more with
+    /// '--help')
+    ///
+    /// Frames of the Android framework, the JDK and Kotlin (`android.*`, `androidx.*`, `java.*`, `kotlin.*`,
+    /// `kotlinx.coroutines.*`, ...) are replaced by a line like `… 12 framework frames`.
+    #[arg(long, value_name("N"), default_value_t = 3, verbatim_doc_comment)]
+    pub collapse_frames: usize,
+
+    /// Packages of your own code, whose stack trace frames are highlighted and never collapsed (see more with '--help')
+    ///
+    /// For example:
+    /// - Passing `com.example.app` will highlight frames of `com.example.app` and its subpackages.
+    /// - Passing `com.example.app,androidx.work` will also keep the frames of `WorkManager`.
+    #[arg(long, value_delimiter(','), value_name("PACKAGE[,...]"), verbatim_doc_comment)]
+    pub my_code: Option<Vec<String>>,
+
+    /// Directory of unstripped native libraries to symbolize the backtraces of native crashes with (see more with
+    /// '--help')
+    ///
+    /// Libraries are looked up at their path on the device, e.g. `DIR/system/lib64/libc.so`, and then by name, e.g.
+    /// `DIR/libc.so`. The function and `file:line` of each frame are resolved with `--symbolizer`.
+    #[arg(long, value_name("DIR"), value_hint(ValueHint::DirPath), verbatim_doc_comment)]
+    pub symbols: Option<String>,
+
+    /// The symbolizer to resolve frames of native backtraces with, which must accept the input of `llvm-symbolizer`.
+    #[arg(long, value_name("COMMAND"), default_value(DEFAULT_SYMBOLIZER), requires("symbols"))]
+    pub symbolizer: String,
+
+    /// R8 or `ProGuard` mapping file to rewrite obfuscated names in stack traces with (see more with '--help')
+    ///
+    /// Can be repeated by passing this argument multiple times, e.g. once for each app.
+    ///
+    /// Frames are rewritten to their original class, method and line, and frames of methods that others were inlined
+    /// into are expanded into a frame for each inlined method. Obfuscated exception classes are rewritten too, as are
+    /// class names in messages that include their package or are printed by `Object.toString()`, e.g. `ehtt@6d1d2b6`.
+    #[arg(long, value_name("FILE"), value_hint(ValueHint::FilePath), verbatim_doc_comment)]
+    pub mapping: Option<Vec<String>>,
+
+    /// Collapse consecutive lines with the same level, tag and message into one, annotated with the number of
+    /// repetitions. Only formatted lines are collapsed, so it can't be used with `--output=logcat` or `--output=jsonl`.
+    #[arg(long, default_value_t = false)]
+    pub dedupe: bool,
+
+    /// Color the keys and values of `key=value` pairs and embedded JSON objects in messages.
+    #[arg(long, default_value_t = false)]
+    pub color_fields: bool,
+
+    /// Ignore numbers and hex addresses when comparing messages for `--dedupe`.
+    #[arg(long, default_value_t = false, requires("dedupe"))]
+    pub dedupe_normalize: bool,
+}
+
+#[derive(Debug, Parser)]
+#[group(required(false), multiple(true))]
+pub struct Saving {
+    /// Save the input lines to FILE as they're read, before filtering.
+    #[arg(long, value_name("FILE"), value_hint(ValueHint::FilePath))]
+    pub save: Option<String>,
+
+    /// Save the log lines that are printed to FILE (see more with '--help')
+    ///
+    /// Lines are saved after filtering and deobfuscation, without being formatted: as JSON Lines like `--output=jsonl`
+    /// if FILE ends with `.jsonl`, and in the `threadtime` format of logcat like `--output=logcat` otherwise.
+    #[arg(long, value_name("FILE"), value_hint(ValueHint::FilePath), verbatim_doc_comment)]
+    pub save_filtered: Option<String>,
+
+    /// Rotate saved files once they reach a size or age (see more with '--help')
+    ///
+    /// Sizes are in `B`, `KB`, `MB` or `GB`, e.g. `100MB`, and ages in `s`, `m`, `h` or `d`, e.g. `1h`. The file is
+    /// renamed to `FILE.1`, the previously rotated files to `FILE.2` and so on, and a new `FILE` is started.
+    #[arg(long, value_name("SIZE|AGE"), verbatim_doc_comment)]
+    pub rotate: Option<Rotation>,
+
+    /// The number of rotated files to keep, removing the oldest ones.
+    #[arg(long, value_name("N"), default_value_t = 5, requires("rotate"))]
+    pub rotate_keep: usize,
+
+    /// Compress rotated files with gzip, to `FILE.1.gz` and so on.
+    #[arg(long, default_value_t = false, requires("rotate"))]
+    pub rotate_compress: bool,
+
+    /// Save the log lines that are printed into one file per group in `--split-dir` (see more with '--help')
+    ///
+    /// - `pid` groups the entries of each process, in files named by the process name when it's known, e.g.
+    ///   `com.example.app-1234.log`, and by the pid otherwise, e.g. `pid-1234.log`.
+    /// - `package` groups the processes of each package, e.g. `com.example.app:remote` with `com.example.app`.
+    /// - `tag`, `level` and `buffer` group the entries by their tag, level, and the buffer they're logged to.
+    ///
+    /// Lines are saved after filtering and deobfuscation, in the format of `--split-format`. Once the input ends,
+    /// `index.tsv` lists the groups with their file and number of lines, from the largest group.
+    #[arg(
+        long,
+        value_name("pid|package|tag|level|buffer"),
+        requires("split_dir"),
+        verbatim_doc_comment
+    )]
+    pub split_by: Option<SplitBy>,
+
+    /// The directory the files of `--split-by` are written to, which is created if it doesn't exist.
+    #[arg(long, value_name("DIR"), value_hint(ValueHint::DirPath), requires("split_by"))]
+    pub split_dir: Option<String>,
+
+    /// The format of the files of `--split-by`: `logcat` for the `threadtime` format of logcat, or `jsonl`.
+    #[arg(long, value_name("logcat|jsonl"), default_value_t = SplitFormat::Logcat, requires("split_by"))]
+    pub split_format: SplitFormat,
+
+    /// The maximum number of files of `--split-by` kept open, closing the least recently written ones.
+    #[arg(long, value_name("N"), default_value_t = 64, requires("split_by"))]
+    pub split_max_open: usize,
+}
+
+#[derive(Debug, Parser)]
+#[group(required(false), multiple(true))]
+pub struct Statistics {
+    /// Print a summary of the entries once the input ends (see more with '--help')
+    ///
+    /// The summary counts the entries read, before filtering, per level, tag, process, package and minute, along with
+    /// the busiest minutes, the peak rate, crashes and ANRs, and the lines that couldn't be parsed. It's printed to
+    /// stderr so that it doesn't mix with the log lines. In live mode, the summary so far is also printed on SIGUSR1,
+    /// or when Enter is pressed, once the next line is read.
+    #[arg(long, default_value_t = false, verbatim_doc_comment)]
+    pub stats: bool,
+
+    /// Only print the summary of `--stats`, to stdout, without the log lines. Lines are still saved.
+    #[arg(long, default_value_t = false)]
+    pub stats_only: bool,
+
+    /// The format of the summary of `--stats`: `table`, colored with the theme, or `json`.
+    #[arg(long, value_name("table|json"), default_value_t = StatsFormat::Table)]
+    pub stats_format: StatsFormat,
+}
@@ -1,48 +1,541 @@
+use std::{
+    env, io,
+    iter::Peekable,
+    path::Path,
+    process::Command,
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+use anyhow::anyhow;
 use clap::Parser;
-use regex::Regex;
+use time::{Duration, UtcDateTime};
 
-use super::Args;
+use super::{Args, args::Commands};
 use crate::{
-    filter::{AndFilter, BoxedLogFilter, LevelFilter, MessageFilter, OrFilter, PidFilter, TagFilter, TidFilter},
-    format::LogcatFormatter,
-    input::LogcatInput,
-    log::LogLine,
-    parse::LogcatParser,
+    app::{AppTracker, ProcessNames},
+    crash::{CrashDetector, CrashReport},
+    dedupe::Deduplicator,
+    fields::FieldCondition,
+    filter::{
+        AndFilter, AppFilter, BoxedLogFilter, CaseSensitivity, FieldFilter, LevelFilter, MatchOptions, MessageFilter,
+        OrFilter, PidFilter, SinceFilter, TagFilter, TidFilter, UntilFilter,
+    },
+    format::{
+        ColorDepth, DEFAULT_PRESET, FormattedLogLine, Highlight, LogcatFormatter, Template, TemplateField, Theme,
+        WrapMode,
+    },
+    input::{InputEvent, LogcatInput},
+    log::{LogEntry, LogLine},
+    output::{
+        HtmlWriter, JsonlWriter, LogcatWriter, OutputFormat, RotatingFile, Serializer, SplitBy, SplitFormat, Splitter,
+    },
+    parse::{CsvParser, InputFormat, InputParser, LogcatParser, parse_csv_columns},
     prelude::*,
+    rate_limit::{Admission, RateLimit, RateLimiter},
+    retrace::{Mapping, Retracer},
+    stacktrace::{FoldedEntry, FrameCollapser},
+    stats::{Stats, StatsFormat},
+    symbolize::Symbolizer,
+    timeline::Timeline,
+    zone::Zone,
 };
 
+/// How long the input may be quiet before the crash being recorded is reported, rather than waiting for the next line.
+const IDLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
 pub fn run() -> Result<()> {
     let args = Args::parse();
-    let input_lines = select_input(&args)?.lines();
-    let parser = LogcatParser::new();
-    let filter = create_filter(&args)?;
-    let formatter = LogcatFormatter::new();
+    let (is_live, input_lines) =
+        select_input(&args).map(|input| (input.is_process(), input.lines_in_background(IDLE_TIMEOUT)))?;
+    let mut raw_log = create_saved_file(&args, args.saving.save.as_deref())?;
+    let mut input_lines = input_lines
+        .inspect(|event| {
+            if let Ok(InputEvent::Line(line)) = event {
+                save_line(&mut raw_log, line);
+            }
+        })
+        .peekable();
+    let app_tracker = create_app_tracker(&args, is_live);
+    let mut parser = create_parser(&args, &mut input_lines)?;
+    let filter = create_filter(&args, app_tracker.as_ref())?;
+    let (formatter, process_names) = create_formatter(&args, is_live)?;
+    let mut crash_detector = create_crash_detector(&args);
+    let mut rate_limiter = create_rate_limiter(&args)?;
+    let mut printer = create_printer(&args, is_live, &formatter, process_names.as_ref())?;
+    let mut summary = create_summary(&args, is_live, &formatter, process_names.as_ref());
+    let stop = watch_for_stop();
+    printer.start();
 
-    for line in input_lines {
-        let line = line?;
+    for event in input_lines.take_while(|_| !stop.load(Ordering::Relaxed)) {
+        if let Some(summary) = &summary {
+            summary.print_if_requested();
+        }
+        let line = match event? {
+            InputEvent::Line(line) => line,
+            // The crash being recorded is reported once the input is quiet, rather than when the next line arrives.
+            InputEvent::Idle => {
+                if let Some(report) = crash_detector.as_mut().and_then(CrashDetector::finish) {
+                    print_crash_report(&mut printer, &report);
+                }
+                continue;
+            },
+        };
         if line.is_empty() {
             continue;
         }
 
-        let log_line = match parser.parse_log_line(&line) {
+        if args.format.source_tz.is_none()
+            && let Some(zone) = Zone::from_bugreport_property(&line)
+        {
+            parser.set_source_zone(zone);
+            continue;
+        }
+
+        let log_lines = match parser.parse_line(&line) {
             Ok(l) => l,
             Err(e) => {
+                if let Some(summary) = &mut summary {
+                    summary.stats.parse_failure();
+                }
                 eprintln!("warning: {e}; see next line\n{line}");
                 continue;
             },
         };
 
-        if let LogLine::Entry(entry) = &log_line
-            && !filter.include_entry(entry)
+        for log_line in log_lines {
+            if let LogLine::Entry(entry) = &log_line {
+                if let Some(process_names) = &process_names {
+                    process_names.observe(entry);
+                }
+                if let Some(summary) = &mut summary {
+                    summary.stats.observe(entry);
+                }
+            }
+
+            if let (LogLine::Entry(entry), Some(app_tracker)) = (&log_line, &app_tracker)
+                && let Some(event) = app_tracker.observe(entry)
+            {
+                printer.print_banner(|formatter| formatter.format_app_event(&event));
+            }
+
+            if let LogLine::Entry(entry) = &log_line
+                && !filter.include_entry(entry)
+            {
+                continue;
+            }
+
+            if let Some(crash_detector) = &mut crash_detector {
+                if let LogLine::Entry(entry) = &log_line {
+                    for report in crash_detector.process(entry) {
+                        print_crash_report(&mut printer, &report);
+                    }
+                }
+                continue;
+            }
+
+            if let (LogLine::Entry(entry), Some(rate_limiter)) = (&log_line, &mut rate_limiter) {
+                match rate_limiter.admit(entry) {
+                    Admission::Suppressed => continue,
+                    Admission::Admitted { suppressed: 0 } => (),
+                    Admission::Admitted { suppressed } => {
+                        printer.flush();
+                        printer.print_banner(|formatter| formatter.format_suppressed(&entry.tag, suppressed));
+                    },
+                }
+            }
+
+            printer.print(log_line);
+        }
+    }
+
+    print_held_back(&mut printer, rate_limiter.as_mut(), crash_detector.as_mut());
+    printer.finish()?;
+    raw_log.as_mut().map(RotatingFile::finish).transpose()?;
+    if let Some(summary) = &summary {
+        summary.print();
+    }
+    Ok(())
+}
+
+/// Returns the flag set on Ctrl-C and SIGTERM, which stop the input so that everything is printed and saved as if it
+/// had ended. Another signal exits right away.
+fn watch_for_stop() -> Arc<AtomicBool> {
+    let stop = Arc::new(AtomicBool::new(false));
+    for &signal in signal_hook::consts::TERM_SIGNALS {
+        let watched = signal_hook::flag::register_conditional_shutdown(signal, 1, Arc::clone(&stop))
+            .and_then(|_| signal_hook::flag::register(signal, Arc::clone(&stop)));
+        if let Err(e) = watched {
+            eprintln!(
+                "warning: failed to watch for signal {signal}: {e}; interrupting nyancat will lose what it holds"
+            );
+        }
+    }
+    stop
+}
+
+fn create_crash_detector(args: &Args) -> Option<CrashDetector> {
+    args.filter
+        .crashes
+        .then(|| CrashDetector::new(Duration::seconds_f64(args.filter.crash_context)))
+}
+
+/// Prints the entries held back once the input ends, along with the suppressed entries and the last crash.
+fn print_held_back(
+    printer: &mut Printer,
+    rate_limiter: Option<&mut RateLimiter>,
+    crash_detector: Option<&mut CrashDetector>,
+) {
+    printer.flush();
+    for (tag, suppressed) in rate_limiter.map(RateLimiter::finish).unwrap_or_default() {
+        printer.print_banner(|formatter| formatter.format_suppressed(&tag, suppressed));
+    }
+
+    if let Some(report) = crash_detector.and_then(CrashDetector::finish) {
+        print_crash_report(printer, &report);
+    }
+}
+
+fn print_crash_report(printer: &mut Printer, report: &CrashReport) {
+    printer.print_banner(|formatter| formatter.format_crash_banner(&report.crash));
+    for entry in report.context.iter().chain(&report.entries) {
+        printer.print(LogLine::Entry(entry.clone()));
+    }
+    printer.flush();
+}
+
+/// Prints log lines, holding entries back while they may be collapsed with the next ones.
+struct Printer<'a> {
+    formatter: &'a LogcatFormatter,
+    retracer: Option<Retracer>,
+    /// Serializes the lines instead of formatting them, with `--output=logcat` and `--output=jsonl`.
+    serializer: Option<Serializer>,
+    /// The file the lines are saved to with `--save-filtered`, and how they're serialized.
+    filtered_log: Option<(RotatingFile, Serializer)>,
+    /// Saves the lines into one file per group, with `--split-by`.
+    splitter: Option<Splitter>,
+    /// Writes the formatted lines as a page, with `--output=html`.
+    html_writer: Option<HtmlWriter>,
+    /// Counts the entries to chart them once the input ends, with `nyancat timeline`.
+    timeline: Option<Timeline>,
+    /// Only saves the lines, with `--stats-only` and `nyancat timeline`.
+    is_quiet: bool,
+    frame_collapser: Option<FrameCollapser>,
+    deduplicator: Option<Deduplicator>,
+}
+
+impl Printer<'_> {
+    fn start(&self) {
+        if let Some(html_writer) = &self.html_writer {
+            print!("{}", html_writer.header());
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(html_writer) = &mut self.html_writer {
+            print!("{}", html_writer.finish());
+        }
+        if let Some((file, _)) = &mut self.filtered_log {
+            file.finish()?;
+        }
+        if let Some(splitter) = &mut self.splitter {
+            splitter.finish()?;
+        }
+        if let Some(timeline) = &self.timeline {
+            let width = terminal_size::terminal_size().map_or(80, |(width, _)| usize::from(width.0));
+            print!("{}", timeline.render(self.formatter.theme(), width));
+        }
+        Ok(())
+    }
+
+    fn print(&mut self, log_line: LogLine) {
+        if let (LogLine::Entry(entry), Some(retracer)) = (&log_line, &self.retracer) {
+            for entry in retracer.retrace(entry) {
+                self.print_retraced(LogLine::Entry(entry));
+            }
+        } else {
+            self.print_retraced(log_line);
+        }
+    }
+
+    fn print_retraced(&mut self, log_line: LogLine) {
+        if let Some((file, serializer)) = &mut self.filtered_log
+            && let Err(e) = file.write_line(&serializer.serialize(&log_line))
         {
-            continue;
+            eprintln!("warning: {e}; no more lines will be saved to it");
+            self.filtered_log = None;
+        }
+        if let Some(splitter) = &mut self.splitter
+            && let Err(e) = splitter.write(&log_line)
+        {
+            eprintln!("warning: {e}; no more lines will be split");
+            self.splitter = None;
+        }
+
+        if let (Some(timeline), LogLine::Entry(entry)) = (&mut self.timeline, &log_line) {
+            timeline.observe(entry);
+        }
+        if self.is_quiet {
+            return;
+        }
+        if let Some(serializer) = &self.serializer {
+            println!("{}", serializer.serialize(&log_line));
+            return;
+        }
+
+        match (log_line, &mut self.frame_collapser) {
+            (LogLine::Entry(entry), Some(frame_collapser)) => {
+                for folded in frame_collapser.push(entry) {
+                    self.print_folded(folded);
+                }
+            },
+            (log_line @ LogLine::Header { .. }, _) => {
+                self.flush();
+                self.write(&self.formatter.format_log_line(&log_line), None);
+            },
+            (LogLine::Entry(entry), None) => self.print_entry(&entry),
         }
+    }
 
-        let formatted_log_line = formatter.format_log_line(&log_line);
-        println!("{formatted_log_line}");
+    fn print_folded(&mut self, folded: FoldedEntry) {
+        match folded {
+            FoldedEntry::Entry(entry) => self.print_entry(&entry),
+            FoldedEntry::CollapsedFrames { first, count } => {
+                self.flush_deduplicator();
+                self.write(&self.formatter.format_collapsed_frames(&first, count), Some(&first));
+            },
+        }
     }
 
-    Ok(())
+    fn print_entry(&mut self, entry: &LogEntry) {
+        let Some(deduplicator) = &mut self.deduplicator else {
+            self.write(&self.formatter.format_log_entry(entry), Some(entry));
+            return;
+        };
+
+        if let Some(repeated) = deduplicator.push(entry) {
+            self.write(&self.formatter.format_repeated_entry(&repeated), Some(&repeated.entry));
+        }
+    }
+
+    /// Prints a line that isn't a log line, like a crash banner, unless the lines are serialized or not printed.
+    fn print_banner(&mut self, format: impl FnOnce(&LogcatFormatter) -> FormattedLogLine) {
+        if self.serializer.is_none() && !self.is_quiet {
+            self.write(&format(self.formatter), None);
+        }
+    }
+
+    /// Writes a formatted line, along with the entry it was formatted from, if any.
+    fn write(&mut self, line: &FormattedLogLine, entry: Option<&LogEntry>) {
+        match &mut self.html_writer {
+            Some(html_writer) => print!("{}", html_writer.write(line, entry)),
+            None => println!("{line}"),
+        }
+    }
+
+    /// Prints the entries held back.
+    fn flush(&mut self) {
+        for folded in self
+            .frame_collapser
+            .as_mut()
+            .map(FrameCollapser::finish)
+            .unwrap_or_default()
+        {
+            self.print_folded(folded);
+        }
+        self.flush_deduplicator();
+    }
+
+    fn flush_deduplicator(&mut self) {
+        if let Some(repeated) = self.deduplicator.as_mut().and_then(Deduplicator::finish) {
+            self.write(&self.formatter.format_repeated_entry(&repeated), Some(&repeated.entry));
+        }
+    }
+}
+
+/// Creates the printer of log lines, writing them as `--output` says.
+fn create_printer<'a>(
+    args: &Args,
+    is_live: bool,
+    formatter: &'a LogcatFormatter,
+    process_names: Option<&Rc<ProcessNames>>,
+) -> Result<Printer<'a>> {
+    let timeline = args.command.as_ref().map(|Commands::Timeline(options)| {
+        Timeline::new(options.style, args.format.display_tz.unwrap_or_else(local_zone))
+            .with_bucket(options.bucket)
+            .with_top_tags(options.top_tags)
+    });
+    if args.format.dedupe && !args.format.output.is_formatted() {
+        return Err(anyhow!(
+            "--dedupe can't be used with --output={}, whose lines aren't formatted",
+            args.format.output
+        ));
+    }
+    let is_quiet = args.statistics.stats_only || timeline.is_some();
+    let is_formatted = args.format.output.is_formatted() && !is_quiet;
+    Ok(Printer {
+        formatter,
+        retracer: create_retracer(args)?,
+        serializer: create_serializer(args, args.format.output, is_live, process_names),
+        filtered_log: create_saved_file(args, args.saving.save_filtered.as_deref())?.and_then(|file| {
+            create_serializer(args, filtered_log_format(args)?, is_live, process_names)
+                .map(|serializer| (file, serializer))
+        }),
+        splitter: create_splitter(args, is_live, process_names)?,
+        html_writer: (args.format.output == OutputFormat::Html && !is_quiet)
+            .then(|| HtmlWriter::new(formatter.theme(), &input_source(args, is_live))),
+        frame_collapser: (is_formatted && args.format.collapse_frames > 0).then(|| {
+            FrameCollapser::new(
+                args.format.collapse_frames,
+                args.format.my_code.clone().unwrap_or_default(),
+            )
+        }),
+        deduplicator: (is_formatted && args.format.dedupe).then(|| Deduplicator::new(args.format.dedupe_normalize)),
+        timeline,
+        is_quiet,
+    })
+}
+
+/// Prints the summary of `--stats` once the input ends, and the summary so far when it's requested.
+struct Summary {
+    stats: Stats,
+    format: StatsFormat,
+    theme: Theme,
+    zone: Zone,
+    /// Whether the summary is printed to stdout, with `--stats-only`, rather than stderr.
+    to_stdout: bool,
+    /// Set on SIGUSR1, or when Enter is pressed in live mode.
+    requested: Arc<AtomicBool>,
+}
+
+impl Summary {
+    fn print(&self) {
+        let summary = self.stats.summary(self.format, &self.theme, self.zone);
+        let summary = summary.trim_end();
+        if self.to_stdout {
+            println!("{summary}");
+        } else {
+            eprintln!("{summary}");
+        }
+    }
+
+    fn print_if_requested(&self) {
+        if self.requested.swap(false, Ordering::Relaxed) {
+            self.print();
+        }
+    }
+}
+
+fn create_summary(
+    args: &Args,
+    is_live: bool,
+    formatter: &LogcatFormatter,
+    process_names: Option<&Rc<ProcessNames>>,
+) -> Option<Summary> {
+    if !args.statistics.stats && !args.statistics.stats_only {
+        return None;
+    }
+
+    let requested = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    if let Err(e) = signal_hook::flag::register(signal_hook::consts::SIGUSR1, Arc::clone(&requested)) {
+        eprintln!("warning: failed to watch for SIGUSR1: {e}");
+    }
+    // Stdin isn't read for lines when they come from `adb logcat`, so it's read for key presses instead.
+    if is_live && !is_piped() {
+        let requested = Arc::clone(&requested);
+        thread::spawn(move || {
+            for _ in io::stdin().lines() {
+                requested.store(true, Ordering::Relaxed);
+            }
+        });
+    }
+
+    Some(Summary {
+        stats: Stats::new().with_process_names(process_names.cloned()),
+        format: args.statistics.stats_format,
+        theme: formatter.theme().clone(),
+        zone: args.format.display_tz.unwrap_or_else(local_zone),
+        to_stdout: args.statistics.stats_only,
+        requested,
+    })
+}
+
+fn create_serializer(
+    args: &Args,
+    format: OutputFormat,
+    is_live: bool,
+    process_names: Option<&Rc<ProcessNames>>,
+) -> Option<Serializer> {
+    match format {
+        OutputFormat::Logcat => Some(Serializer::Logcat(LogcatWriter::new(
+            args.format.display_tz.unwrap_or_else(local_zone),
+        ))),
+        OutputFormat::Jsonl => Some(Serializer::Jsonl(JsonlWriter::new(
+            process_names.cloned(),
+            device_serial(is_live),
+            input_source(args, is_live),
+        ))),
+        OutputFormat::Text | OutputFormat::Plain | OutputFormat::Html => None,
+    }
+}
+
+/// Returns the format lines are saved in with `--save-filtered`, which depends on the extension of the file.
+fn filtered_log_format(args: &Args) -> Option<OutputFormat> {
+    let path = args.saving.save_filtered.as_deref()?;
+    let is_jsonl = Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("jsonl"));
+    Some(if is_jsonl {
+        OutputFormat::Jsonl
+    } else {
+        OutputFormat::Logcat
+    })
+}
+
+fn create_splitter(args: &Args, is_live: bool, process_names: Option<&Rc<ProcessNames>>) -> Result<Option<Splitter>> {
+    let (Some(split_by), Some(split_dir)) = (args.saving.split_by, &args.saving.split_dir) else {
+        return Ok(None);
+    };
+
+    let format = match args.saving.split_format {
+        SplitFormat::Logcat => OutputFormat::Logcat,
+        SplitFormat::Jsonl => OutputFormat::Jsonl,
+    };
+    let serializer = create_serializer(args, format, is_live, process_names).expect("split formats are serialized");
+    let splitter = Splitter::new(split_by, Path::new(split_dir), serializer, args.saving.split_format)?
+        .with_process_names(process_names.cloned())
+        .with_max_open_files(args.saving.split_max_open)
+        .with_flush_lines(args.input.input_file.is_none());
+    Ok(Some(splitter))
+}
+
+/// Creates a file lines are saved to, rotated as `--rotate` says. Lines of live input are flushed as they're saved.
+fn create_saved_file(args: &Args, path: Option<&str>) -> Result<Option<RotatingFile>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+
+    let file = RotatingFile::create(Path::new(path), args.input.input_file.is_none())?;
+    Ok(Some(file.with_rotation(
+        args.saving.rotate,
+        args.saving.rotate_keep,
+        args.saving.rotate_compress,
+    )))
+}
+
+/// Saves `line` to `file`, which is no longer written to once writing to it fails.
+fn save_line(file: &mut Option<RotatingFile>, line: &str) {
+    if let Some(saved) = file
+        && let Err(e) = saved.write_line(line)
+    {
+        eprintln!("warning: {e}; no more lines will be saved to it");
+        *file = None;
+    }
 }
 
 fn select_input(args: &Args) -> Result<LogcatInput> {
@@ -64,13 +557,158 @@ fn select_input(args: &Args) -> Result<LogcatInput> {
     LogcatInput::from_process()
 }
 
-fn create_filter(args: &Args) -> Result<BoxedLogFilter> {
+fn create_app_tracker(args: &Args, is_live: bool) -> Option<Rc<AppTracker>> {
+    let app_tracker = AppTracker::new(args.filter.app.as_ref()?);
+
+    if is_live && let Err(e) = app_tracker.track_running_processes() {
+        eprintln!("warning: {e}; the app's processes will only be tracked once they restart");
+    }
+
+    Some(Rc::new(app_tracker))
+}
+
+/// Creates the parser for the format of the input, which is detected from the first line with `--input-format=auto`.
+/// The header of CSV input is consumed.
+fn create_parser(
+    args: &Args,
+    input_lines: &mut Peekable<impl Iterator<Item = Result<InputEvent>>>,
+) -> Result<InputParser> {
+    while matches!(input_lines.peek(), Some(Ok(InputEvent::Idle))) {
+        input_lines.next();
+    }
+    let first_line = match input_lines.peek() {
+        Some(Ok(InputEvent::Line(line))) => line.as_str(),
+        _ => "",
+    };
+    // Column mappings only apply to CSV, so they imply it.
+    let input_format = match args.parsing.input_format {
+        InputFormat::Auto if args.parsing.csv_columns.is_some() => InputFormat::Csv,
+        InputFormat::Logcat | InputFormat::Jsonl if args.parsing.csv_columns.is_some() => {
+            return Err(anyhow!(
+                "--csv-columns can't be used with --input-format={}",
+                args.parsing.input_format
+            ));
+        },
+        input_format => input_format.detect(first_line),
+    };
+    let mut parser = match input_format {
+        InputFormat::Auto | InputFormat::Logcat => InputParser::Logcat(LogcatParser::new()),
+        InputFormat::Jsonl => InputParser::Jsonl,
+        InputFormat::Csv => {
+            let columns = parse_csv_columns(args.parsing.csv_columns.as_deref().unwrap_or_default())?;
+            let header = match input_lines.next().transpose()? {
+                Some(InputEvent::Line(header)) => header,
+                _ => String::new(),
+            };
+            InputParser::Csv(CsvParser::new(&header, &columns)?)
+        },
+    };
+    parser.set_source_zone(args.format.source_tz.unwrap_or_else(local_zone));
+    Ok(parser)
+}
+
+/// Returns the local time zone, or UTC if it can't be determined. Times are then displayed as they were logged unless
+/// `--source-tz` or `--display-tz` are passed, which fail rather than guess when they name the local time zone.
+fn local_zone() -> Zone {
+    Zone::local().unwrap_or(Zone::Utc)
+}
+
+/// Creates the formatter, along with the process names it uses if the template contains `{process}`, which must be
+/// updated with every entry.
+fn create_formatter(args: &Args, is_live: bool) -> Result<(LogcatFormatter, Option<Rc<ProcessNames>>)> {
+    colored::control::set_override(args.format.output != OutputFormat::Plain && args.format.color.is_enabled());
+    // Pages are displayed by browsers, which display any color, and lay out long lines themselves.
+    let is_html = args.format.output == OutputFormat::Html;
+    let color_depth = if is_html {
+        ColorDepth::TrueColor
+    } else {
+        args.format.color_depth.unwrap_or_else(ColorDepth::detect)
+    };
+    let template = Template::new(args.format.template.as_deref().unwrap_or(DEFAULT_PRESET))?;
+    // Serialized entries name their process whenever it's known.
+    let process_names = (template.uses(TemplateField::Process)
+        || args.format.output == OutputFormat::Jsonl
+        || filtered_log_format(args) == Some(OutputFormat::Jsonl)
+        || args.saving.split_format == SplitFormat::Jsonl
+        || matches!(args.saving.split_by, Some(SplitBy::Pid | SplitBy::Package))
+        || args.statistics.stats
+        || args.statistics.stats_only)
+        .then(|| create_process_names(is_live));
+    let device = template
+        .uses(TemplateField::Device)
+        .then(|| device_serial(is_live))
+        .flatten();
+    let formatter = LogcatFormatter::new()
+        .with_template(template)
+        .with_process_names(process_names.clone())
+        .with_origin(device, input_source(args, is_live))
+        .with_time_mode(args.format.time, args.format.delta_by)
+        .with_display_zone(args.format.display_tz.unwrap_or_else(local_zone))
+        .with_gaps(
+            Duration::seconds_f64(args.format.slow_gap),
+            args.format.gap_marker.map(Duration::seconds_f64),
+        )
+        .with_theme(load_theme(args)?.degraded(color_depth))
+        .with_color_depth(color_depth)
+        .with_tag_layout(args.format.tag_width, args.format.tag_align)
+        .with_wrap(if is_html { WrapMode::None } else { args.format.wrap })
+        .with_my_code(args.format.my_code.clone().unwrap_or_default())
+        .with_symbolizer(
+            args.format
+                .symbols
+                .as_ref()
+                .map(|symbols| Symbolizer::new(&args.format.symbolizer, Path::new(symbols))),
+        )
+        .with_highlights(create_highlights(args)?)
+        .with_colored_fields(args.format.color_fields)
+        .with_hashed_colors(args.format.color_by.clone().unwrap_or_default());
+
+    Ok((formatter, process_names))
+}
+
+fn create_process_names(is_live: bool) -> Rc<ProcessNames> {
+    let process_names = ProcessNames::new();
+
+    if is_live && let Err(e) = process_names.track_running_processes() {
+        eprintln!("warning: {e}; processes will only be named once they restart");
+    }
+
+    Rc::new(process_names)
+}
+
+/// Returns the serial of the device the entries come from: `ANDROID_SERIAL`, or the device `adb` is connected to.
+fn device_serial(is_live: bool) -> Option<String> {
+    if let Ok(serial) = env::var("ANDROID_SERIAL") {
+        return Some(serial);
+    }
+    if !is_live {
+        return None;
+    }
+
+    let output = Command::new("adb").arg("get-serialno").output().ok()?;
+    let serial = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    (output.status.success() && !serial.is_empty()).then_some(serial)
+}
+
+fn input_source(args: &Args, is_live: bool) -> String {
+    match &args.input.input_file {
+        Some(input_file) => input_file.clone(),
+        None if is_live => "adb logcat".to_owned(),
+        None => "stdin".to_owned(),
+    }
+}
+
+fn create_filter(args: &Args, app_tracker: Option<&Rc<AppTracker>>) -> Result<BoxedLogFilter> {
     let mut filters: Vec<BoxedLogFilter> = Vec::new();
 
     if let Some(pid) = args.filter.pid {
         filters.push(Box::new(PidFilter(pid)));
     }
 
+    if let Some(app_tracker) = app_tracker {
+        filters.push(Box::new(AppFilter(Rc::clone(app_tracker))));
+    }
+
     if let Some(tid) = args.filter.tid {
         filters.push(Box::new(TidFilter(tid)));
     }
@@ -79,10 +717,24 @@ fn create_filter(args: &Args) -> Result<BoxedLogFilter> {
         filters.push(Box::new(LevelFilter(level)));
     }
 
+    // Times are read on the clocks they're printed on, in the year logcat times are assumed to be logged in.
+    let zone = args.format.display_tz.unwrap_or_else(local_zone);
+    let year = UtcDateTime::now().year();
+    if let Some(since) = &args.filter.since {
+        filters.push(Box::new(SinceFilter(zone.parse_datetime(since, year)?)));
+    }
+    if let Some(until) = &args.filter.until {
+        filters.push(Box::new(UntilFilter(zone.parse_datetime(until, year)?)));
+    }
+
     if let Some(tags) = &args.filter.tag {
         let mut tag_filters = Vec::<BoxedLogFilter>::new();
         for tag in tags {
-            tag_filters.push(Box::new(TagFilter::new(tag)?));
+            tag_filters.push(Box::new(TagFilter::new(
+                tag,
+                args.matching.tag_match,
+                match_options(args),
+            )?));
         }
         filters.push(Box::new(OrFilter(tag_filters)));
     }
@@ -90,14 +742,80 @@ fn create_filter(args: &Args) -> Result<BoxedLogFilter> {
     if let Some(messages) = &args.filter.message {
         let mut message_filters = Vec::<BoxedLogFilter>::new();
         for message in messages {
-            message_filters.push(Box::new(MessageFilter(Regex::new(message)?)));
+            message_filters.push(Box::new(MessageFilter::new(message, match_options(args))?));
         }
         filters.push(Box::new(OrFilter(message_filters)));
     }
 
+    for condition in args.filter.field.iter().flatten() {
+        filters.push(Box::new(FieldFilter(FieldCondition::new(
+            condition,
+            match_options(args),
+        )?)));
+    }
+
     Ok(Box::new(AndFilter(filters)))
 }
 
+const fn match_options(args: &Args) -> MatchOptions {
+    let case = if args.matching.ignore_case {
+        CaseSensitivity::Insensitive
+    } else if args.matching.smart_case {
+        CaseSensitivity::Smart
+    } else {
+        CaseSensitivity::Sensitive
+    };
+
+    MatchOptions {
+        case,
+        fixed_strings: args.matching.fixed_strings,
+    }
+}
+
+fn create_rate_limiter(args: &Args) -> Result<Option<RateLimiter>> {
+    let Some(rate_limits) = &args.filter.rate_limit else {
+        return Ok(None);
+    };
+
+    let rate_limits = rate_limits
+        .iter()
+        .map(|rate_limit| RateLimit::new(rate_limit, match_options(args)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(RateLimiter::new(rate_limits)))
+}
+
+fn load_theme(args: &Args) -> Result<Theme> {
+    if let Some(theme) = &args.format.theme {
+        return Theme::builtin(theme).map_or_else(|| Theme::load(Path::new(theme)), Ok);
+    }
+
+    match Theme::user_theme_path() {
+        Some(path) if path.exists() => Theme::load(&path),
+        _ => Ok(Theme::dark()),
+    }
+}
+
+fn create_retracer(args: &Args) -> Result<Option<Retracer>> {
+    let Some(paths) = &args.format.mapping else {
+        return Ok(None);
+    };
+
+    let mappings = paths
+        .iter()
+        .map(|path| Mapping::load(Path::new(path)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(Retracer::new(mappings)))
+}
+
+fn create_highlights(args: &Args) -> Result<Vec<Highlight>> {
+    args.format
+        .highlight
+        .iter()
+        .flatten()
+        .map(|highlight| Highlight::new(highlight))
+        .collect()
+}
+
 fn is_piped() -> bool {
     !atty::is(atty::Stream::Stdin)
 }
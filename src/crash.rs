@@ -0,0 +1,278 @@
+use std::collections::VecDeque;
+
+use lazy_regex::{Lazy, regex};
+use regex::Regex;
+use strum::Display;
+use time::{Duration, UtcDateTime};
+
+use crate::{log::LogEntry, stacktrace};
+
+const JAVA_PROCESS_REGEX: &Lazy<Regex> = regex!(r"^Process: ([^,\s]+), PID: (\d+)");
+const NATIVE_PROCESS_REGEX: &Lazy<Regex> = regex!(r"^pid: (\d+), tid: \d+, name: .*>>> (\S+) <<<");
+const ANR_PROCESS_REGEX: &Lazy<Regex> = regex!(r"^ANR in (\S+)");
+const ANR_PID_REGEX: &Lazy<Regex> = regex!(r"^PID: (\d+)");
+const FORCE_FINISHING_REGEX: &Lazy<Regex> = regex!(r"^Force finishing activity ([^/\s]+)");
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Display)]
+pub enum CrashKind {
+    #[strum(serialize = "FATAL EXCEPTION")]
+    Java,
+    #[strum(serialize = "NATIVE CRASH")]
+    Native,
+    #[strum(serialize = "ANR")]
+    Anr,
+    #[strum(serialize = "FORCE FINISHING ACTIVITY")]
+    ForceFinishing,
+}
+
+impl CrashKind {
+    /// Returns the kind of crash `entry` is the first line of the report of, if any.
+    pub fn detect(entry: &LogEntry) -> Option<Self> {
+        let message = entry.message.as_str();
+
+        if message.starts_with("FATAL EXCEPTION") {
+            Some(Self::Java)
+        } else if (entry.tag == "libc" && message.starts_with("Fatal signal"))
+            || (entry.tag == "DEBUG" && message.starts_with("*** *** ***"))
+        {
+            Some(Self::Native)
+        } else if ANR_PROCESS_REGEX.is_match(message) {
+            Some(Self::Anr)
+        } else if FORCE_FINISHING_REGEX.is_match(message) {
+            Some(Self::ForceFinishing)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the crash is reported by several consecutive lines, rather than a single one.
+    const fn is_multiline(self) -> bool {
+        !matches!(self, Self::ForceFinishing)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Crash {
+    pub kind: CrashKind,
+    pub process: Option<String>,
+    pub pid: u32,
+    pub datetime: UtcDateTime,
+}
+
+/// A detected crash, with the lines that reported it and the lines its process logged shortly before.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CrashReport {
+    pub crash: Crash,
+    pub context: Vec<LogEntry>,
+    pub entries: Vec<LogEntry>,
+}
+
+/// Detects Java and native crashes, ANRs and force finished activities in a stream of log entries.
+///
+/// Reports are only produced once all of a crash's lines have been seen, which is when a line from another source
+/// arrives, or the input ends.
+pub struct CrashDetector {
+    context_duration: Duration,
+    history: VecDeque<LogEntry>,
+    record: Option<(CrashKind, Vec<LogEntry>)>,
+}
+
+impl CrashDetector {
+    pub const fn new(context_duration: Duration) -> Self {
+        Self {
+            context_duration,
+            history: VecDeque::new(),
+            record: None,
+        }
+    }
+
+    pub fn process(&mut self, entry: &LogEntry) -> Vec<CrashReport> {
+        let mut reports = Vec::new();
+
+        if let Some((_, entries)) = &mut self.record {
+            if Self::continues_record(&entries[0], entry) {
+                entries.push(entry.clone());
+                return reports;
+            }
+            reports.extend(self.finish());
+        }
+
+        if let Some(kind) = CrashKind::detect(entry) {
+            self.record = Some((kind, vec![entry.clone()]));
+            if !kind.is_multiline() {
+                reports.extend(self.finish());
+            }
+            return reports;
+        }
+
+        while let Some(oldest) = self.history.front()
+            && entry.datetime - oldest.datetime > self.context_duration
+        {
+            self.history.pop_front();
+        }
+        self.history.push_back(entry.clone());
+
+        reports
+    }
+
+    /// Reports the crash currently being recorded, if any, regardless of whether all of its lines were seen.
+    pub fn finish(&mut self) -> Option<CrashReport> {
+        let (kind, entries) = self.record.take()?;
+        let crash = Self::identify_crash(kind, &entries);
+        let context = self
+            .history
+            .iter()
+            .filter(|e| e.pid == crash.pid && crash.datetime - e.datetime <= self.context_duration)
+            .cloned()
+            .collect();
+
+        Some(CrashReport {
+            crash,
+            context,
+            entries,
+        })
+    }
+
+    fn continues_record(first: &LogEntry, entry: &LogEntry) -> bool {
+        // The tombstone of a native crash is logged by crash_dump, from another process than its fatal signal.
+        if first.tag == "libc" && entry.tag == "DEBUG" {
+            return true;
+        }
+        let is_stacktrace = stacktrace::is_stacktrace_entry(entry) || stacktrace::is_stacktrace_cause(entry);
+        entry.pid == first.pid && (entry.tag == first.tag || is_stacktrace)
+    }
+
+    fn identify_crash(kind: CrashKind, entries: &[LogEntry]) -> Crash {
+        let first = &entries[0];
+        let mut crash = Crash {
+            kind,
+            process: None,
+            pid: first.pid,
+            datetime: first.datetime,
+        };
+
+        for entry in entries {
+            let message = entry.message.as_str();
+            if let Some(captures) = JAVA_PROCESS_REGEX.captures(message) {
+                crash.process = Some(captures[1].to_owned());
+                crash.pid = captures[2].parse().unwrap_or(crash.pid);
+            } else if let Some(captures) = NATIVE_PROCESS_REGEX.captures(message) {
+                crash.pid = captures[1].parse().unwrap_or(crash.pid);
+                crash.process = Some(captures[2].to_owned());
+            } else if let Some(captures) = ANR_PROCESS_REGEX.captures(message) {
+                crash.process = Some(captures[1].to_owned());
+            } else if let Some(captures) = ANR_PID_REGEX.captures(message) {
+                crash.pid = captures[1].parse().unwrap_or(crash.pid);
+            } else if let Some(captures) = FORCE_FINISHING_REGEX.captures(message) {
+                crash.process = Some(captures[1].to_owned());
+            }
+        }
+
+        crash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time_macros::utc_datetime;
+
+    use super::*;
+    use crate::{log::LogLine, parse::LogcatParser};
+
+    fn detect(lines: &str) -> Vec<CrashReport> {
+        let parser = LogcatParser::with_year(2025);
+        let mut detector = CrashDetector::new(Duration::seconds(5));
+        let mut reports = Vec::new();
+
+        for line in lines.lines() {
+            if let LogLine::Entry(entry) = parser.parse_log_line(line).unwrap() {
+                reports.extend(detector.process(&entry));
+            }
+        }
+        reports.extend(detector.finish());
+        reports
+    }
+
+    #[test]
+    fn test_detect_java_crash_with_context() {
+        let reports = detect(
+            "10-01 12:10:30.000  4375  4375 I Foo: too early
+10-01 12:10:40.000  4375  4375 I Foo: about to crash
+10-01 12:10:40.500  4000  4000 I Bar: other process
+10-01 12:10:41.000  4375  4375 E AndroidRuntime: FATAL EXCEPTION: main
+10-01 12:10:41.000  4375  4375 E AndroidRuntime: Process: com.example.foo, PID: 4375
+10-01 12:10:41.000  4375  4375 E AndroidRuntime: java.lang.IllegalStateException: oops
+10-01 12:10:41.000  4375  4375 E AndroidRuntime: 	at com.example.foo.Main.onCreate(Main.java:12)
+10-01 12:10:41.100  4000  4000 I Bar: after the crash",
+        );
+
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(
+            report.crash,
+            Crash {
+                kind: CrashKind::Java,
+                process: Some("com.example.foo".to_owned()),
+                pid: 4375,
+                datetime: utc_datetime!(2025-10-01 12:10:41.000),
+            }
+        );
+        assert_eq!(report.entries.len(), 4);
+        assert_eq!(report.context.len(), 1);
+        assert_eq!(report.context[0].message, "about to crash");
+    }
+
+    #[test]
+    fn test_detect_anr_and_native_crash() {
+        let reports = detect(
+            "10-01 12:10:40.000  1515  1600 E ActivityManager: ANR in com.example.foo (com.example.foo/.Main)
+10-01 12:10:40.000  1515  1600 E ActivityManager: PID: 4375
+10-01 12:10:40.000  1515  1600 E ActivityManager: Reason: Input dispatching timed out
+10-01 12:10:41.000  5000  5000 F DEBUG: *** *** *** *** *** *** *** *** *** *** *** *** *** *** *** ***
+10-01 12:10:41.000  5000  5000 F DEBUG: pid: 4400, tid: 4410, name: RenderThread  >>> com.example.bar <<<
+10-01 12:10:42.000  1515  1600 W ActivityManager: Force finishing activity com.example.bar/.Main",
+        );
+
+        let crashes = reports
+            .iter()
+            .map(|r| (r.crash.kind, r.crash.pid))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            crashes,
+            vec![
+                (CrashKind::Anr, 4375),
+                (CrashKind::Native, 4400),
+                (CrashKind::ForceFinishing, 1515)
+            ]
+        );
+        assert_eq!(reports[1].crash.process.as_deref(), Some("com.example.bar"));
+    }
+
+    #[test]
+    fn test_detect_native_crash_from_fatal_signal() {
+        let reports = detect(
+            "10-01 12:10:40.000  4400  4400 I Bar: about to crash
+10-01 12:10:41.000  4400  4410 F libc: Fatal signal 11 (SIGSEGV), code 1 (SEGV_MAPERR), fault addr 0x0 in tid 4410 (RenderThread), pid 4400 (com.example.bar)
+10-01 12:10:41.100  5000  5000 F DEBUG: *** *** *** *** *** *** *** *** *** *** *** *** *** *** *** ***
+10-01 12:10:41.100  5000  5000 F DEBUG: pid: 4400, tid: 4410, name: RenderThread  >>> com.example.bar <<<
+10-01 12:10:41.100  5000  5000 F DEBUG: signal 11 (SIGSEGV), code 1 (SEGV_MAPERR), fault addr 0x0
+10-01 12:10:42.000  1000  1000 I Foo: after the crash",
+        );
+
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(
+            report.crash,
+            Crash {
+                kind: CrashKind::Native,
+                process: Some("com.example.bar".to_owned()),
+                pid: 4400,
+                datetime: utc_datetime!(2025-10-01 12:10:41.000),
+            }
+        );
+        assert_eq!(report.entries.len(), 4);
+        assert_eq!(report.entries[0].tag, "libc");
+        assert_eq!(report.context.len(), 1);
+        assert_eq!(report.context[0].message, "about to crash");
+    }
+}
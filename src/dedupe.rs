@@ -0,0 +1,146 @@
+use std::borrow::Cow;
+
+use lazy_regex::{Lazy, regex};
+use regex::Regex;
+use time::{Duration, UtcDateTime};
+
+use crate::log::LogEntry;
+
+const NUMBER_REGEX: &Lazy<Regex> = regex!(r"0[xX][0-9a-fA-F]+|\d+");
+
+/// An entry that was logged `count` times in a row, the last time at `last_datetime`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RepeatedEntry {
+    pub entry: LogEntry,
+    pub count: usize,
+    pub last_datetime: UtcDateTime,
+}
+
+impl RepeatedEntry {
+    pub fn duration(&self) -> Duration {
+        self.last_datetime - self.entry.datetime
+    }
+}
+
+/// Collapses consecutive entries with the same level, tag and message into a single [`RepeatedEntry`].
+///
+/// An entry is only returned once a different entry arrives, or the input ends.
+pub struct Deduplicator {
+    normalize: bool,
+    pending: Option<RepeatedEntry>,
+}
+
+impl Deduplicator {
+    pub const fn new(normalize: bool) -> Self {
+        Self {
+            normalize,
+            pending: None,
+        }
+    }
+
+    pub fn push(&mut self, entry: &LogEntry) -> Option<RepeatedEntry> {
+        let normalize = self.normalize;
+        if let Some(pending) = &mut self.pending
+            && pending.entry.level == entry.level
+            && pending.entry.tag == entry.tag
+            && Self::key(&pending.entry.message, normalize) == Self::key(&entry.message, normalize)
+        {
+            pending.count += 1;
+            pending.last_datetime = entry.datetime;
+            return None;
+        }
+
+        self.pending.replace(RepeatedEntry {
+            entry: entry.clone(),
+            count: 1,
+            last_datetime: entry.datetime,
+        })
+    }
+
+    pub const fn finish(&mut self) -> Option<RepeatedEntry> {
+        self.pending.take()
+    }
+
+    /// Returns the part of `message` that must be equal for two entries to be considered repetitions.
+    ///
+    /// When normalizing, numbers and hex addresses are ignored, so `err 12 at 0x7f3a` repeats `err 13 at 0x7f3b`.
+    fn key(message: &str, normalize: bool) -> Cow<'_, str> {
+        if normalize {
+            NUMBER_REGEX.replace_all(message, "#")
+        } else {
+            Cow::Borrowed(message)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::LogLevel;
+
+    #[test]
+    fn test_collapse_consecutive_entries() {
+        let mut deduplicator = Deduplicator::new(false);
+        let messages = ["err write", "err write", "err write", "other", "err write"];
+
+        let mut repeated = messages
+            .iter()
+            .enumerate()
+            .filter_map(|(i, message)| {
+                let seconds = Duration::seconds(i64::try_from(i).unwrap());
+                deduplicator.push(&LogEntry::test("FileUtils", message).after(seconds))
+            })
+            .collect::<Vec<_>>();
+        repeated.extend(deduplicator.finish());
+
+        let counts = repeated
+            .iter()
+            .map(|r| (r.entry.message.as_str(), r.count))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![("err write", 3), ("other", 1), ("err write", 1)]);
+        assert_eq!(repeated[0].duration(), Duration::seconds(2));
+    }
+
+    #[test]
+    fn test_collapse_normalized_entries() {
+        let mut deduplicator = Deduplicator::new(true);
+
+        let first = LogEntry::test("FileUtils", "binder 12 at 0xb400007d87527a00");
+        let second = LogEntry::test("FileUtils", "binder 13 at 0xb400007d87527a08").after(Duration::SECOND);
+        assert_eq!(deduplicator.push(&first), None);
+        assert_eq!(deduplicator.push(&second), None);
+
+        let repeated = deduplicator.finish().unwrap();
+        assert_eq!(repeated.count, 2);
+        assert_eq!(repeated.entry.message, "binder 12 at 0xb400007d87527a00");
+    }
+
+    #[test]
+    fn test_keep_entries_of_different_levels() {
+        let mut deduplicator = Deduplicator::new(false);
+        let levels = [LogLevel::Warning, LogLevel::Warning, LogLevel::Error, LogLevel::Fatal];
+
+        let mut repeated = levels
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &level)| {
+                let seconds = Duration::seconds(i64::try_from(i).unwrap());
+                deduplicator.push(
+                    &LogEntry::test("FileUtils", "err write")
+                        .with_level(level)
+                        .after(seconds),
+                )
+            })
+            .collect::<Vec<_>>();
+        repeated.extend(deduplicator.finish());
+
+        let counts = repeated
+            .iter()
+            .map(|r| (r.entry.level, r.count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            counts,
+            vec![(LogLevel::Warning, 2), (LogLevel::Error, 1), (LogLevel::Fatal, 1)]
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::cmp::Ordering;
+
+use anyhow::anyhow;
+use chumsky::prelude::*;
+use regex::Regex;
+
+use super::{FieldValue, Fields};
+use crate::{filter::MatchOptions, prelude::*};
+
+type ParserErr<'src> = extra::Err<Rich<'src, char>>;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Source {
+    /// A `key=value` pair, referenced as `field.<key>`.
+    Pair,
+    /// A value of an embedded JSON object, referenced as `json.<path>`.
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Matches,
+}
+
+/// A condition on a message's structured fields, e.g. `field.tclass == "file"` or `json.status >= 500`.
+///
+/// A condition without an operator, e.g. `field.uid`, only requires the field to exist. The patterns of `~` are
+/// matched with `options`, like those of other filters.
+#[derive(Debug)]
+pub struct FieldCondition {
+    source: Source,
+    key: String,
+    comparison: Option<(Operator, FieldValue)>,
+    regex: Option<Regex>,
+}
+
+impl FieldCondition {
+    pub fn new(condition: &str, options: MatchOptions) -> Result<Self> {
+        let (source, key, comparison) = condition_parser()
+            .parse(condition)
+            .into_result()
+            .map_err(|errors| {
+                anyhow!(
+                    "failed to parse field condition `{condition}`: {}",
+                    errors
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                )
+            })?;
+
+        let regex = match &comparison {
+            Some((Operator::Matches, value)) => Some(
+                options
+                    .build_regex(&value.to_string())
+                    .map_err(|e| anyhow!("failed to compile field condition `{condition}`: {e}"))?,
+            ),
+            _ => None,
+        };
+
+        Ok(Self {
+            source,
+            key,
+            comparison,
+            regex,
+        })
+    }
+
+    pub fn is_met_by(&self, fields: &Fields) -> bool {
+        let values = match self.source {
+            Source::Pair => &fields.pairs,
+            Source::Json => &fields.json,
+        };
+        let Some(value) = values.get(&self.key) else {
+            return false;
+        };
+        let Some((operator, expected)) = &self.comparison else {
+            return true;
+        };
+
+        if let Some(regex) = &self.regex {
+            return regex.is_match(&value.to_string());
+        }
+
+        let Some(ordering) = value.compare(expected) else {
+            return *operator == Operator::Ne;
+        };
+        match operator {
+            Operator::Eq => ordering.is_eq(),
+            Operator::Ne => ordering.is_ne(),
+            Operator::Lt => ordering.is_lt(),
+            Operator::Le => ordering.is_le(),
+            Operator::Gt => ordering.is_gt(),
+            Operator::Ge => ordering.is_ge(),
+            Operator::Matches => unreachable!("regex conditions are handled above"),
+        }
+    }
+}
+
+impl FieldValue {
+    /// Compares two values, converting strings to numbers when compared with a number.
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Number(a), Self::Number(b)) => a.partial_cmp(b),
+            (Self::String(a), Self::Number(b)) => a.parse::<f64>().ok()?.partial_cmp(b),
+            (Self::Number(a), Self::String(b)) => a.partial_cmp(&b.parse::<f64>().ok()?),
+            (Self::String(a), Self::String(b)) => Some(a.cmp(b)),
+            (Self::Bool(a), Self::Bool(b)) => Some(a.cmp(b)),
+            (Self::Null, Self::Null) => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String(string) => write!(f, "{string}"),
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Bool(bool) => write!(f, "{bool}"),
+            Self::Null => write!(f, "null"),
+        }
+    }
+}
+
+fn condition_parser<'src>()
+-> impl Parser<'src, &'src str, (Source, String, Option<(Operator, FieldValue)>), ParserErr<'src>> {
+    let source = choice((just("field").to(Source::Pair), just("json").to(Source::Json)));
+    let key = any()
+        .filter(|c: &char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        .repeated()
+        .at_least(1)
+        .to_slice()
+        .map(ToOwned::to_owned);
+
+    source
+        .then_ignore(just('.'))
+        .then(key)
+        .then(operator_parser().padded().then(value_parser()).or_not())
+        .then_ignore(text::whitespace())
+        .then_ignore(end())
+        .map(|((source, key), comparison)| (source, key, comparison))
+}
+
+fn operator_parser<'src>() -> impl Parser<'src, &'src str, Operator, ParserErr<'src>> {
+    choice((
+        just("==").to(Operator::Eq),
+        just("!=").to(Operator::Ne),
+        just("<=").to(Operator::Le),
+        just(">=").to(Operator::Ge),
+        just("<").to(Operator::Lt),
+        just(">").to(Operator::Gt),
+        just("~").to(Operator::Matches),
+    ))
+}
+
+fn value_parser<'src>() -> impl Parser<'src, &'src str, FieldValue, ParserErr<'src>> {
+    let quoted = none_of('"')
+        .repeated()
+        .to_slice()
+        .delimited_by(just('"'), just('"'))
+        .map(|s: &str| FieldValue::String(s.to_owned()));
+    let unquoted = any()
+        .filter(|c: &char| !c.is_whitespace())
+        .repeated()
+        .at_least(1)
+        .to_slice()
+        .map(|s: &str| match s {
+            "true" => FieldValue::Bool(true),
+            "false" => FieldValue::Bool(false),
+            "null" => FieldValue::Null,
+            _ => s
+                .parse()
+                .map_or_else(|_| FieldValue::String(s.to_owned()), FieldValue::Number),
+        });
+
+    quoted.or(unquoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::CaseSensitivity;
+
+    #[test]
+    fn test_field_conditions() {
+        let fields = Fields::extract(r#"avc: denied tclass=file ino=1260 {"status": 503, "retry": false}"#);
+        let is_met = |condition: &str| {
+            FieldCondition::new(condition, MatchOptions::default())
+                .unwrap()
+                .is_met_by(&fields)
+        };
+
+        assert!(is_met(r#"field.tclass == "file""#));
+        assert!(is_met("field.tclass==file"));
+        assert!(is_met("field.tclass != dir"));
+        assert!(is_met("field.ino > 1000"));
+        assert!(!is_met("field.ino < 1000"));
+        assert!(is_met("field.ino"));
+        assert!(!is_met("field.uid"));
+        assert!(is_met("json.status >= 500"));
+        assert!(is_met("json.retry == false"));
+        assert!(is_met("json.status ~ ^5\\d\\d$"));
+        assert!(!is_met("json.tclass == file"));
+    }
+
+    #[test]
+    fn test_field_condition_match_options() {
+        let fields = Fields::extract("avc: denied tclass=file path=/data/a.b");
+        let is_met = |condition: &str, options: MatchOptions| {
+            FieldCondition::new(condition, options)
+                .unwrap()
+                .is_met_by(&fields)
+        };
+        let ignore_case = MatchOptions {
+            case: CaseSensitivity::Insensitive,
+            ..MatchOptions::default()
+        };
+        let smart_case = MatchOptions {
+            case: CaseSensitivity::Smart,
+            ..MatchOptions::default()
+        };
+        let fixed_strings = MatchOptions {
+            fixed_strings: true,
+            ..MatchOptions::default()
+        };
+
+        assert!(!is_met("field.tclass ~ FILE", MatchOptions::default()));
+        assert!(is_met("field.tclass ~ FILE", ignore_case));
+        assert!(is_met("field.tclass ~ fi", smart_case));
+        assert!(!is_met("field.tclass ~ Fi", smart_case));
+        assert!(is_met("field.path ~ ^/data", MatchOptions::default()));
+        assert!(!is_met("field.path ~ ^/data", fixed_strings));
+        assert!(is_met("field.path ~ a.b", fixed_strings));
+    }
+
+    #[test]
+    fn test_invalid_field_conditions() {
+        let invalid_conditions = vec![
+            "tclass == file",      // missing source
+            "field.",              // missing key
+            "field.tclass =",      // invalid operator
+            "field.tclass ==",     // missing value
+            "field.tclass ~ (",    // invalid regex
+            "field.tclass == a b", // trailing input
+        ];
+
+        for condition in invalid_conditions {
+            assert!(
+                FieldCondition::new(condition, MatchOptions::default()).is_err(),
+                "Expected error for: {condition}"
+            );
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::{collections::HashMap, ops::Range};
+
+use lazy_regex::{Lazy, regex};
+use regex::Regex;
+use serde_json::Value;
+
+const KEY_VALUE_REGEX: &Lazy<Regex> = regex!(r#"(?:^|[\s,;(\[{])([A-Za-z_][\w.\-]*)=("(?:[^"\\]|\\.)*"|[^\s"]+)"#);
+const JSON_KEY_REGEX: &Lazy<Regex> = regex!(r#"("(?:[^"\\]|\\.)*")\s*:"#);
+const JSON_SCALAR_REGEX: &Lazy<Regex> = regex!(r#":\s*("(?:[^"\\]|\\.)*"|-?\d[\d.eE+\-]*|true|false|null)"#);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+impl FieldValue {
+    /// Parses an unquoted `key=value` value, which is a number or a boolean if it looks like one.
+    fn from_unquoted(text: &str) -> Self {
+        match text {
+            "true" => Self::Bool(true),
+            "false" => Self::Bool(false),
+            _ => text
+                .parse()
+                .map_or_else(|_| Self::String(text.to_owned()), Self::Number),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FieldSpan {
+    Key,
+    Value,
+}
+
+/// Structured data found in a message: `key=value` pairs and embedded JSON objects.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Fields {
+    /// Values of `key=value` pairs, by key. When a key repeats, its first value is kept.
+    pub pairs: HashMap<String, FieldValue>,
+    /// Scalar values of embedded JSON objects, by their dotted path, e.g. `error.code` or `items.0`.
+    pub json: HashMap<String, FieldValue>,
+    /// Byte ranges of the keys and values in the message, sorted by position.
+    pub spans: Vec<(Range<usize>, FieldSpan)>,
+}
+
+impl Fields {
+    pub fn extract(message: &str) -> Self {
+        let mut fields = Self::default();
+        let json_ranges = fields.extract_json(message);
+
+        for captures in KEY_VALUE_REGEX.captures_iter(message) {
+            let (key, value) = (captures.get(1).unwrap(), captures.get(2).unwrap());
+            if json_ranges.iter().any(|range| range.contains(&key.start())) {
+                continue;
+            }
+
+            let (parsed, value_range) = if value.as_str().starts_with('"') {
+                let quoted = value.as_str().trim_matches('"');
+                (FieldValue::String(quoted.to_owned()), value.range())
+            } else {
+                let text = value.as_str().trim_end_matches([',', ';', ')', ']', '}']);
+                (
+                    FieldValue::from_unquoted(text),
+                    value.start()..value.start() + text.len(),
+                )
+            };
+
+            fields
+                .pairs
+                .entry(key.as_str().to_owned())
+                .or_insert(parsed);
+            fields.spans.push((key.range(), FieldSpan::Key));
+            fields.spans.push((value_range, FieldSpan::Value));
+        }
+
+        fields.spans.sort_by_key(|(range, _)| range.start);
+        fields
+    }
+
+    /// Extracts the JSON objects embedded in `message`, returning their byte ranges.
+    fn extract_json(&mut self, message: &str) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        let mut position = 0;
+
+        while let Some(offset) = message[position..].find('{') {
+            let start = position + offset;
+            let mut stream = serde_json::Deserializer::from_str(&message[start..]).into_iter::<Value>();
+
+            if let Some(Ok(value @ Value::Object(_))) = stream.next() {
+                let end = start + stream.byte_offset();
+                Self::flatten_json(&mut self.json, String::new(), &value);
+                self.push_json_spans(message, start..end);
+                ranges.push(start..end);
+                position = end;
+            } else {
+                position = start + 1;
+            }
+        }
+
+        ranges
+    }
+
+    fn flatten_json(json: &mut HashMap<String, FieldValue>, path: String, value: &Value) {
+        let join = |key: &str| {
+            if path.is_empty() {
+                key.to_owned()
+            } else {
+                format!("{path}.{key}")
+            }
+        };
+
+        let scalar = match value {
+            Value::Object(object) => {
+                for (key, value) in object {
+                    Self::flatten_json(json, join(key), value);
+                }
+                return;
+            },
+            Value::Array(array) => {
+                for (index, value) in array.iter().enumerate() {
+                    Self::flatten_json(json, join(&index.to_string()), value);
+                }
+                return;
+            },
+            Value::String(string) => FieldValue::String(string.clone()),
+            Value::Number(number) => number.as_f64().map_or(FieldValue::Null, FieldValue::Number),
+            Value::Bool(bool) => FieldValue::Bool(*bool),
+            Value::Null => FieldValue::Null,
+        };
+        json.entry(path).or_insert(scalar);
+    }
+
+    fn push_json_spans(&mut self, message: &str, range: Range<usize>) {
+        let json = &message[range.clone()];
+        let keys = JSON_KEY_REGEX
+            .captures_iter(json)
+            .map(|captures| (captures.get(1).unwrap().range(), FieldSpan::Key));
+        let values = JSON_SCALAR_REGEX
+            .captures_iter(json)
+            .map(|captures| (captures.get(1).unwrap().range(), FieldSpan::Value));
+
+        let spans = keys
+            .chain(values)
+            .map(|(span, kind)| (span.start + range.start..span.end + range.start, kind));
+        self.spans.extend(spans);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_key_value_pairs() {
+        let message = r#"type=1400 audit(0.0:1427): avc: denied { read } for name="u:object_r:system_adbd_prop:s0" dev="tmpfs" ino=1260 scontext=u:r:gmscore_app:s0:c512,c768 tclass=file permissive=0"#;
+        let fields = Fields::extract(message);
+
+        assert_eq!(fields.pairs["type"], FieldValue::Number(1400.0));
+        assert_eq!(
+            fields.pairs["name"],
+            FieldValue::String("u:object_r:system_adbd_prop:s0".to_owned())
+        );
+        assert_eq!(
+            fields.pairs["scontext"],
+            FieldValue::String("u:r:gmscore_app:s0:c512,c768".to_owned())
+        );
+        assert_eq!(fields.pairs["tclass"], FieldValue::String("file".to_owned()));
+        assert_eq!(fields.pairs.len(), 7);
+        assert!(fields.json.is_empty());
+
+        let (key, _) = &fields.spans[0];
+        assert_eq!(&message[key.clone()], "type");
+        let (value, _) = &fields.spans[5];
+        assert_eq!(&message[value.clone()], r#""tmpfs""#);
+    }
+
+    #[test]
+    fn test_extract_embedded_json() {
+        let message = r#"request done: {"status": 503, "error": {"retry": true, "codes": [1, 2]}} (pid=4375)"#;
+        let fields = Fields::extract(message);
+
+        assert_eq!(fields.json["status"], FieldValue::Number(503.0));
+        assert_eq!(fields.json["error.retry"], FieldValue::Bool(true));
+        assert_eq!(fields.json["error.codes.1"], FieldValue::Number(2.0));
+        assert_eq!(fields.pairs["pid"], FieldValue::Number(4375.0));
+
+        let spans = fields
+            .spans
+            .iter()
+            .map(|(range, kind)| (&message[range.clone()], *kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            vec![
+                (r#""status""#, FieldSpan::Key),
+                ("503", FieldSpan::Value),
+                (r#""error""#, FieldSpan::Key),
+                (r#""retry""#, FieldSpan::Key),
+                ("true", FieldSpan::Value),
+                (r#""codes""#, FieldSpan::Key),
+                ("pid", FieldSpan::Key),
+                ("4375", FieldSpan::Value),
+            ]
+        );
+    }
+}
@@ -0,0 +1,5 @@
+mod condition;
+mod extract;
+
+pub use condition::FieldCondition;
+pub use extract::{FieldSpan, FieldValue, Fields};
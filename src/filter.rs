@@ -1,7 +1,13 @@
+use std::rc::Rc;
+
 use anyhow::anyhow;
 use regex::{Regex, RegexBuilder};
+use strum::{Display, EnumString};
+use time::UtcDateTime;
 
 use crate::{
+    app::AppTracker,
+    fields::{FieldCondition, Fields},
     log::{LogEntry, LogLevel},
     prelude::*,
 };
@@ -44,6 +50,14 @@ impl LogFilter for PidFilter {
     }
 }
 
+pub struct AppFilter(pub Rc<AppTracker>);
+
+impl LogFilter for AppFilter {
+    fn include_entry(&self, log_entry: &LogEntry) -> bool {
+        self.0.is_tracked(log_entry.pid)
+    }
+}
+
 pub struct TidFilter(pub u32);
 
 impl LogFilter for TidFilter {
@@ -60,27 +74,211 @@ impl LogFilter for LevelFilter {
     }
 }
 
+/// Includes the entries logged at or after a time.
+pub struct SinceFilter(pub UtcDateTime);
+
+impl LogFilter for SinceFilter {
+    fn include_entry(&self, log_entry: &LogEntry) -> bool {
+        log_entry.datetime >= self.0
+    }
+}
+
+/// Includes the entries logged before a time.
+pub struct UntilFilter(pub UtcDateTime);
+
+impl LogFilter for UntilFilter {
+    fn include_entry(&self, log_entry: &LogEntry) -> bool {
+        log_entry.datetime < self.0
+    }
+}
+
+pub struct FieldFilter(pub FieldCondition);
+
+impl LogFilter for FieldFilter {
+    fn include_entry(&self, log_entry: &LogEntry) -> bool {
+        self.0.is_met_by(&Fields::extract(&log_entry.message))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum CaseSensitivity {
+    #[default]
+    Sensitive,
+    Insensitive,
+    /// Case insensitive, unless the pattern contains an uppercase character.
+    Smart,
+}
+
+/// Options controlling how the patterns passed to regex-based filters are interpreted.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct MatchOptions {
+    pub case: CaseSensitivity,
+    /// Treat patterns as literal strings rather than regexes.
+    pub fixed_strings: bool,
+}
+
+impl MatchOptions {
+    /// Compiles `pattern` into a regex that searches for it anywhere in the text.
+    pub fn build_regex(self, pattern: &str) -> Result<Regex> {
+        let regex = if self.fixed_strings {
+            regex::escape(pattern)
+        } else {
+            pattern.to_owned()
+        };
+        self.compile(&regex, pattern)
+    }
+
+    /// Compiles `pattern` into a regex that must match the entire text.
+    pub fn build_whole_regex(self, pattern: &str) -> Result<Regex> {
+        let regex = if self.fixed_strings {
+            regex::escape(pattern)
+        } else {
+            pattern.to_owned()
+        };
+        self.compile(&format!("^(?:{regex})$"), pattern)
+    }
+
+    fn compile(self, regex: &str, pattern: &str) -> Result<Regex> {
+        let case_insensitive = match self.case {
+            CaseSensitivity::Sensitive => false,
+            CaseSensitivity::Insensitive => true,
+            CaseSensitivity::Smart => !pattern.chars().any(char::is_uppercase),
+        };
+
+        RegexBuilder::new(regex)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|e| anyhow!("failed to compile regex: {e}"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum TagMatch {
+    /// The tag must be equal to the pattern.
+    Exact,
+    /// The tag must match the pattern, where `*` matches any characters and `?` matches a single character.
+    Glob,
+    /// The tag must match the pattern as a regex in its entirety.
+    #[default]
+    Regex,
+    /// The tag must contain the pattern.
+    Substring,
+}
+
 pub struct TagFilter(Regex);
 
 impl TagFilter {
-    pub fn new(regex: &str) -> Result<Self> {
-        let regex = RegexBuilder::new(&format!("^{regex}$"))
-            .build()
-            .map_err(|e| anyhow!("failed to compile regex: {e}"))?;
+    pub fn new(pattern: &str, tag_match: TagMatch, options: MatchOptions) -> Result<Self> {
+        let literal = MatchOptions {
+            fixed_strings: true,
+            ..options
+        };
+
+        let regex = match tag_match {
+            TagMatch::Exact => literal.build_whole_regex(pattern)?,
+            TagMatch::Glob => options.compile(&glob_to_regex(pattern), pattern)?,
+            TagMatch::Regex => options.build_whole_regex(pattern)?,
+            TagMatch::Substring => literal.build_regex(pattern)?,
+        };
         Ok(Self(regex))
     }
 }
 
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
 impl LogFilter for TagFilter {
     fn include_entry(&self, log_entry: &LogEntry) -> bool {
         self.0.is_match(&log_entry.tag)
     }
 }
 
-pub struct MessageFilter(pub Regex);
+pub struct MessageFilter(Regex);
+
+impl MessageFilter {
+    pub fn new(pattern: &str, options: MatchOptions) -> Result<Self> {
+        Ok(Self(options.build_regex(pattern)?))
+    }
+}
 
 impl LogFilter for MessageFilter {
     fn include_entry(&self, log_entry: &LogEntry) -> bool {
         self.0.is_match(&log_entry.message)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use time_macros::utc_datetime;
+
+    use super::*;
+    use crate::zone::Zone;
+
+    #[test]
+    fn test_time_range_filters_in_zone() {
+        // Logged at 14:10:45.588 on the clocks of Berlin.
+        let entry = LogEntry::test("Finsky", "").with_datetime(utc_datetime!(2025-10-01 12:10:45.588));
+        let berlin = "Europe/Berlin".parse::<Zone>().unwrap();
+        let time = |time: &str| berlin.parse_datetime(time, 2025).unwrap();
+
+        assert!(SinceFilter(time("10-01 14:10")).include_entry(&entry));
+        assert!(!SinceFilter(time("10-01 14:11")).include_entry(&entry));
+        assert!(UntilFilter(time("10-01 14:11")).include_entry(&entry));
+        assert!(!UntilFilter(time("10-01 14:10:45.588")).include_entry(&entry));
+        assert!(!UntilFilter(time("10-01 12:11")).include_entry(&entry));
+    }
+
+    #[test]
+    fn test_tag_filter_match_modes() {
+        let tag = LogEntry::test("com.xiaomi.xmsf", "");
+        let includes = |pattern: &str, tag_match: TagMatch, options: MatchOptions| {
+            TagFilter::new(pattern, tag_match, options)
+                .unwrap()
+                .include_entry(&tag)
+        };
+        let options = MatchOptions::default();
+
+        assert!(includes("com.xiaomi.xmsf", TagMatch::Exact, options));
+        assert!(!includes("com.xiaomi", TagMatch::Exact, options));
+        assert!(includes("com.*.xmsf", TagMatch::Glob, options));
+        assert!(includes("com.xiaomi.xms?", TagMatch::Glob, options));
+        assert!(!includes("xiaomi.*", TagMatch::Glob, options));
+        assert!(includes("com\\.\\w+\\.xmsf|Finsky", TagMatch::Regex, options));
+        assert!(includes("xiaomi", TagMatch::Substring, options));
+        assert!(!includes("x.aomi", TagMatch::Substring, options));
+    }
+
+    #[test]
+    fn test_case_sensitivity_and_fixed_strings() {
+        let message = LogEntry::test("Finsky", "[89] AU2 RequiredVehicleState is missing.");
+        let includes = |pattern: &str, case: CaseSensitivity, fixed_strings: bool| {
+            let options = MatchOptions { case, fixed_strings };
+            MessageFilter::new(pattern, options)
+                .unwrap()
+                .include_entry(&message)
+        };
+
+        assert!(!includes("requiredvehiclestate", CaseSensitivity::Sensitive, false));
+        assert!(includes("requiredvehiclestate", CaseSensitivity::Insensitive, false));
+        assert!(includes("requiredvehiclestate", CaseSensitivity::Smart, false));
+        assert!(!includes("RequiredVehicleSTATE", CaseSensitivity::Smart, false));
+        assert!(includes("[89] AU2", CaseSensitivity::Sensitive, true));
+        assert!(!includes("[89] AU2", CaseSensitivity::Sensitive, false));
+        assert!(
+            !TagFilter::new("fINSKY", TagMatch::Exact, MatchOptions::default())
+                .unwrap()
+                .include_entry(&message)
+        );
+    }
+}
@@ -1,153 +1,814 @@
-use std::cell::Cell;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    ops::Range,
+    rc::Rc,
+};
 
 use colored::{Color, ColoredString, Colorize};
-use lazy_regex::{Lazy, regex};
-use nonempty_collections::nev;
-use regex::Regex;
-use time::{UtcDateTime, format_description::BorrowedFormatItem};
+use nonempty_collections::{NEVec, nev};
+use time::{
+    Duration, UtcDateTime,
+    format_description::{BorrowedFormatItem, OwnedFormatItem},
+};
 use time_macros::format_description;
 
-use super::{colors, log_line::FormattedLogLine};
+use super::{
+    highlight::{Highlight, highlighted_ranges},
+    layout::{self, MAX_AUTO_TAG_WIDTH, TagAlign, TagWidth},
+    log_line::{FormattedLogLine, FormattedPart},
+    palette::{self, ColorBy, ColorDepth},
+    template::{DEFAULT_PRESET, FieldWidth, Template, TemplateField, TemplateItem},
+    theme::{Style, Theme},
+    timing::{self, Clock, DeltaBy, Intervals, TimeMode},
+    wrap::{self, LineWrap, TerminalWidth, WrapMode},
+};
 use crate::{
-    log::{LogEntry, LogLevel, LogLine},
+    app::{AppEvent, ProcessNames},
+    crash::Crash,
+    dedupe::RepeatedEntry,
+    fields::{FieldSpan, Fields},
+    log::{LogEntry, LogLine},
     parse::MISSING_TAG,
+    stacktrace::{Exception, ExceptionTree, Relation, StacktraceLine},
+    symbolize::Symbolizer,
+    tombstone::{NativeFrame, TombstoneLine},
+    zone::Zone,
 };
 
 const DATETIME_FORMAT: &[BorrowedFormatItem] =
     format_description!("[month]-[day] [hour]:[minute]:[second].[subsecond digits:3]");
 
-const MAX_TAG_LENGTH: usize = 1;
+/// How much further than the exception they belong to the frames of stack traces are indented.
+const STACKTRACE_ENTRY_INDENT: usize = 4;
+
+/// Precedes `Caused by:` and `Suppressed:` lines, one branch further than the exception they belong to.
+const TREE_BRANCH: &str = "└─ ";
+const TREE_BRANCH_WIDTH: usize = 3;
 
-const STACKTRACE_ENTRY_REGEX: &Lazy<Regex> = regex!(r"^\s+((at)|(\.{3})) ");
-const STACKTRACE_ENTRY_START: &str = "                                         ";
+/// The width of the library column of native backtraces, which grows up to the maximum for longer paths, so that a
+/// single long path doesn't push every function to the edge of the terminal.
+const MIN_NATIVE_LIBRARY_WIDTH: usize = 32;
+const MAX_NATIVE_LIBRARY_WIDTH: usize = 48;
 
-const STACKTRACE_CAUSE_REGEX: &Lazy<Regex> = regex!(r"^\s*Caused by: ");
-const STACKTRACE_CAUSE_START: &str = "                                   ";
+/// The default time since the previous entry from which it's styled as a gap.
+const DEFAULT_SLOW_GAP: Duration = Duration::SECOND;
 
 pub struct LogcatFormatter {
+    template: Template,
+    /// The column at which the message of the last regular entry started.
+    message_column: Cell<usize>,
+    clock: Clock,
+    time_mode: TimeMode,
+    /// The time zone times are displayed in.
+    display_zone: Zone,
+    slow_gap: Duration,
+    /// The time between entries from which a gap marker is printed between them.
+    gap_marker: Option<Duration>,
+    /// The tree of exceptions of the stack trace being logged by each thread, by pid and tid, which is dropped once
+    /// the thread logs anything else.
+    exception_trees: RefCell<HashMap<(u32, u32), ExceptionTree>>,
+    /// Packages whose stack trace frames are highlighted.
+    my_code: Vec<String>,
+    symbolizer: Option<Symbolizer>,
+    /// The current widths of the program counter and library columns of native backtraces.
+    native_columns: Cell<(usize, usize)>,
+    /// The current width of the tag column, which only changes with [`TagWidth::Auto`].
     tag_length: Cell<usize>,
+    tag_width: TagWidth,
+    tag_align: TagAlign,
+    theme: Theme,
+    highlights: Vec<Highlight>,
+    color_fields: bool,
+    color_by: Vec<ColorBy>,
+    color_depth: ColorDepth,
+    wrap_mode: WrapMode,
+    /// The width of the terminal, or `None` if long lines are left as they are.
+    terminal_width: Option<TerminalWidth>,
+    process_names: Option<Rc<ProcessNames>>,
+    device: Option<String>,
+    source: String,
 }
 
 impl LogcatFormatter {
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
         Self {
+            template: Template::new(DEFAULT_PRESET).expect("presets are valid templates"),
+            message_column: Cell::new(0),
+            clock: Clock::new(None),
+            time_mode: TimeMode::Absolute,
+            display_zone: Zone::Utc,
+            slow_gap: DEFAULT_SLOW_GAP,
+            gap_marker: None,
+            exception_trees: RefCell::new(HashMap::new()),
+            my_code: Vec::new(),
+            symbolizer: None,
+            native_columns: Cell::new((0, MIN_NATIVE_LIBRARY_WIDTH)),
             tag_length: Cell::new(0),
+            tag_width: TagWidth::Auto,
+            tag_align: TagAlign::Left,
+            theme: Theme::dark(),
+            highlights: Vec::new(),
+            color_fields: false,
+            color_by: Vec::new(),
+            color_depth: ColorDepth::Ansi16,
+            wrap_mode: WrapMode::None,
+            terminal_width: None,
+            process_names: None,
+            device: None,
+            source: String::new(),
         }
     }
 
+    pub fn with_template(mut self, template: Template) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Names the processes of the `{process}` template field.
+    pub fn with_process_names(mut self, process_names: Option<Rc<ProcessNames>>) -> Self {
+        self.process_names = process_names;
+        self
+    }
+
+    /// Sets the `{device}` and `{source}` template fields.
+    pub fn with_origin(mut self, device: Option<String>, source: String) -> Self {
+        self.device = device;
+        self.source = source;
+        self
+    }
+
+    /// Sets what the `{time}` field shows, and whether times since the previous entry are measured per process or
+    /// thread.
+    pub fn with_time_mode(mut self, time_mode: TimeMode, delta_by: Option<DeltaBy>) -> Self {
+        self.time_mode = time_mode;
+        self.clock = Clock::new(delta_by);
+        self
+    }
+
+    pub const fn with_display_zone(mut self, display_zone: Zone) -> Self {
+        self.display_zone = display_zone;
+        self
+    }
+
+    /// Styles times since the previous entry longer than `slow_gap` as gaps, and prints a marker before entries
+    /// logged longer than `gap_marker` after the previous one.
+    pub const fn with_gaps(mut self, slow_gap: Duration, gap_marker: Option<Duration>) -> Self {
+        self.slow_gap = slow_gap;
+        self.gap_marker = gap_marker;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub const fn with_tag_layout(mut self, tag_width: TagWidth, tag_align: TagAlign) -> Self {
+        if let TagWidth::Fixed(width) = tag_width {
+            self.tag_length = Cell::new(width);
+        }
+        self.tag_width = tag_width;
+        self.tag_align = tag_align;
+        self
+    }
+
+    /// Fits log entries into the width of the terminal, if stdout is one, using the given mode.
+    pub fn with_wrap(mut self, wrap_mode: WrapMode) -> Self {
+        self.wrap_mode = wrap_mode;
+        self.terminal_width = (wrap_mode != WrapMode::None).then(TerminalWidth::new);
+        self
+    }
+
+    pub fn with_my_code(mut self, my_code: Vec<String>) -> Self {
+        self.my_code = my_code;
+        self
+    }
+
+    /// Resolves the function and `file:line` of frames of native backtraces with the given symbolizer.
+    pub fn with_symbolizer(mut self, symbolizer: Option<Symbolizer>) -> Self {
+        self.symbolizer = symbolizer;
+        self
+    }
+
+    pub fn with_highlights(mut self, highlights: Vec<Highlight>) -> Self {
+        self.highlights = highlights;
+        self
+    }
+
+    pub const fn with_colored_fields(mut self, color_fields: bool) -> Self {
+        self.color_fields = color_fields;
+        self
+    }
+
+    /// Colors each value of the `color_by` elements with its own color.
+    pub fn with_hashed_colors(mut self, color_by: Vec<ColorBy>) -> Self {
+        self.color_by = color_by;
+        self
+    }
+
+    /// Restricts the colors picked by the formatter to those a terminal with the given depth can display. Colors of
+    /// the theme should already be degraded to this depth.
+    pub const fn with_color_depth(mut self, color_depth: ColorDepth) -> Self {
+        self.color_depth = color_depth;
+        self
+    }
+
+    pub const fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
     pub fn format_log_line(&self, log_line: &LogLine) -> FormattedLogLine {
         match log_line {
-            LogLine::Header(header) => Self::format_log_header(header),
+            LogLine::Header { buffer } => self.format_log_header(buffer),
             LogLine::Entry(entry) => self.format_log_entry(entry),
         }
     }
 
-    fn format_log_header(header: &str) -> FormattedLogLine {
-        format!("--------- beginning of {header}")
-            .color(colors::HEADER)
+    pub fn format_app_event(&self, event: &AppEvent) -> FormattedLogLine {
+        match event {
+            AppEvent::Started { process, pid } => self
+                .theme
+                .app_started
+                .apply(format!("--------- {process} (pid {pid}) started")),
+            AppEvent::Died { process, pid } => self
+                .theme
+                .app_died
+                .apply(format!("--------- {process} (pid {pid}) died")),
+        }
+        .to_log_line()
+    }
+
+    pub fn format_crash_banner(&self, crash: &Crash) -> FormattedLogLine {
+        let process = crash.process.as_deref().unwrap_or("unknown process");
+        let datetime = self.datetime_to_string(crash.datetime);
+        self.theme
+            .crash
+            .apply(format!(
+                "--------- {} in {process} (pid {}) at {datetime}",
+                crash.kind, crash.pid
+            ))
             .to_log_line()
     }
 
-    fn format_log_entry(&self, entry: &LogEntry) -> FormattedLogLine {
-        if Self::is_stacktrace_entry(entry) {
-            Self::format_stacktrace_entry(entry)
-        } else if Self::is_stacktrace_cause(entry) {
-            Self::format_stacktrace_cause(entry)
-        } else {
-            self.format_regular_entry(entry)
+    pub fn format_suppressed(&self, tag: &str, count: usize) -> FormattedLogLine {
+        self.theme
+            .suppressed
+            .apply(format!(
+                "--------- suppressed {count} lines of {tag} exceeding the rate limit"
+            ))
+            .to_log_line()
+    }
+
+    pub fn format_repeated_entry(&self, repeated: &RepeatedEntry) -> FormattedLogLine {
+        let mut formatted = self.format_log_entry(&repeated.entry);
+        if repeated.count > 1 {
+            let seconds = repeated.duration().as_seconds_f64();
+            let annotation = format!("(×{} over {seconds:.1}s)", repeated.count);
+            formatted.push(self.theme.repetitions.apply(annotation).into());
         }
+        formatted
     }
 
-    fn is_stacktrace_entry(entry: &LogEntry) -> bool {
-        STACKTRACE_ENTRY_REGEX.is_match(&entry.message)
+    fn format_log_header(&self, buffer: &str) -> FormattedLogLine {
+        self.theme
+            .header
+            .apply(format!("--------- beginning of {buffer}"))
+            .to_log_line()
     }
 
-    fn is_stacktrace_cause(entry: &LogEntry) -> bool {
-        STACKTRACE_CAUSE_REGEX.is_match(&entry.message)
+    pub fn format_log_entry(&self, entry: &LogEntry) -> FormattedLogLine {
+        self.format_entry(entry, None)
     }
 
-    fn format_stacktrace_entry(entry: &LogEntry) -> FormattedLogLine {
-        let tag_padding = " ".repeat(entry.tag.len());
-        format!("{STACKTRACE_ENTRY_START}{tag_padding}{}", entry.message.trim_start())
-            .color(Self::color_of_level(entry.level))
-            .dimmed()
-            .to_log_line()
+    /// Formats a run of `count` framework frames, starting with `first`, as a single line.
+    pub fn format_collapsed_frames(&self, first: &LogEntry, count: usize) -> FormattedLogLine {
+        self.format_entry(first, Some(count))
     }
 
-    fn format_stacktrace_cause(entry: &LogEntry) -> FormattedLogLine {
-        let tag_padding = " ".repeat(entry.tag.len());
-        format!("{STACKTRACE_CAUSE_START}{tag_padding}{}", entry.message.trim_start())
-            .color(Self::color_of_level(entry.level))
-            .to_log_line()
+    fn format_entry(&self, entry: &LogEntry, collapsed_frames: Option<usize>) -> FormattedLogLine {
+        let intervals = self.clock.observe(entry);
+        let line = StacktraceLine::parse(&entry.message);
+        // Threads log their stack traces at the same time, so each has a tree of its own.
+        let thread = (entry.pid, entry.tid);
+        if line.is_none() {
+            self.exception_trees.borrow_mut().remove(&thread);
+        }
+        let depth = line.as_ref().map_or(0, |line| {
+            self.exception_trees
+                .borrow_mut()
+                .entry(thread)
+                .or_default()
+                .observe(line)
+        });
+        let (mut formatted, indent) = match (TombstoneLine::parse(entry), line) {
+            (Some(TombstoneLine::Frame(frame)), _) => {
+                let column = self.message_column() + STACKTRACE_ENTRY_INDENT;
+                (self.format_native_frame(entry, frame, column), column)
+            },
+            (
+                _,
+                None
+                | Some(StacktraceLine::Exception(Exception {
+                    relation: Relation::Thrown,
+                    ..
+                })),
+            ) => (self.format_regular_entry(entry, intervals), self.message_column()),
+            (_, Some(StacktraceLine::Exception(exception))) => (
+                self.format_stacktrace_cause(entry, exception, depth),
+                self.tree_column(depth),
+            ),
+            (_, Some(line)) => {
+                let column = self.tree_column(depth) + STACKTRACE_ENTRY_INDENT;
+                (
+                    self.format_stacktrace_frame(entry, line, column, collapsed_frames),
+                    column,
+                )
+            },
+        };
+
+        if let Some(gap_marker) = self.gap_marker
+            && intervals.gap > gap_marker
+        {
+            let seconds = intervals.gap.as_seconds_f64();
+            formatted.set_marker(
+                self.theme
+                    .gap
+                    .apply(format!("--------- {seconds:.3}s since the previous line")),
+            );
+        }
+
+        if let Some(width) = self.terminal_width.as_ref().and_then(TerminalWidth::get) {
+            formatted.set_wrap(LineWrap {
+                mode: self.wrap_mode,
+                width,
+                indent,
+            });
+        }
+        formatted
     }
 
-    fn format_regular_entry(&self, entry: &LogEntry) -> FormattedLogLine {
-        let parts = nev![
-            Self::format_datetime(&entry.datetime),
-            Self::format_pid(entry.pid),
-            Self::format_tid(entry.tid),
-            Self::format_log_level(entry.level),
-            self.format_tag(&entry.tag),
-            Self::format_message(&entry.message, entry.level),
+    /// Formats a frame, or a run of `collapsed_frames` frames, starting at `column`.
+    fn format_stacktrace_frame(
+        &self,
+        entry: &LogEntry,
+        line: StacktraceLine,
+        column: usize,
+        collapsed_frames: Option<usize>,
+    ) -> FormattedLogLine {
+        let style = self
+            .theme
+            .level(entry.level)
+            .message
+            .overlay(self.theme.stacktrace_entry);
+        let mut spans = vec![ColoredString::from(" ".repeat(column))];
+
+        match (line, collapsed_frames) {
+            (_, Some(count)) => spans.push(
+                self.theme
+                    .suppressed
+                    .apply(format!("… {count} framework frames")),
+            ),
+            (StacktraceLine::Frame(frame), None) => {
+                let (class_style, method_style) = if frame.is_in(&self.my_code) {
+                    let my_code_style = style.overlay(self.theme.my_code_frame);
+                    (my_code_style, my_code_style)
+                } else {
+                    (
+                        style.overlay(self.theme.frame_class),
+                        style.overlay(self.theme.frame_method),
+                    )
+                };
+                spans.extend([
+                    style.apply("at "),
+                    style.overlay(self.theme.frame_package).apply(frame.package),
+                    class_style.apply(frame.class),
+                    style.apply("."),
+                    method_style.apply(frame.method),
+                    style.apply("("),
+                    style
+                        .overlay(self.theme.frame_location)
+                        .apply(frame.location),
+                    style.apply(")"),
+                ]);
+            },
+            (StacktraceLine::Continuation { text, .. }, None) => spans.push(style.apply(text)),
+            (StacktraceLine::Exception(_), None) => unreachable!("exceptions are not frames"),
+        }
+
+        spans.retain(|span| !span.is_empty());
+        Self::spans_to_log_line(spans)
+    }
+
+    /// Formats a frame of a native backtrace starting at `column`, with the program counter, library and function in
+    /// aligned columns, and the function and `file:line` resolved by the symbolizer if there is one.
+    fn format_native_frame(&self, entry: &LogEntry, frame: NativeFrame, column: usize) -> FormattedLogLine {
+        let style = self
+            .theme
+            .level(entry.level)
+            .message
+            .overlay(self.theme.stacktrace_entry);
+        let symbol = self
+            .symbolizer
+            .as_ref()
+            .zip(frame.pc_value())
+            .and_then(|(symbolizer, pc)| symbolizer.symbolize(frame.library, pc));
+        let library = frame.file_offset.map_or_else(
+            || frame.library.to_owned(),
+            |offset| format!("{} (offset {offset})", frame.library),
+        );
+
+        let (pc_width, library_width) = self.native_columns.get();
+        let pc_width = pc_width.max(frame.pc.len());
+        let library_width = library_width.max(wrap::display_width(&library).min(MAX_NATIVE_LIBRARY_WIDTH));
+        self.native_columns.set((pc_width, library_width));
+
+        let mut spans = vec![
+            ColoredString::from(" ".repeat(column)),
+            style.apply(format!("#{} pc {:<pc_width$}  ", frame.index, frame.pc)),
+            style
+                .overlay(self.theme.frame_package)
+                .apply(format!("{library:<library_width$}")),
+            style.apply("  "),
         ];
-        FormattedLogLine::new(parts)
+        match symbol {
+            Some(symbol) => {
+                spans.push(
+                    style
+                        .overlay(self.theme.frame_method)
+                        .apply(symbol.function),
+                );
+                if let Some(location) = symbol.location {
+                    spans.push(style.apply("  "));
+                    spans.push(style.overlay(self.theme.frame_location).apply(location));
+                }
+            },
+            None => spans.push(
+                style
+                    .overlay(self.theme.frame_method)
+                    .apply(frame.function.unwrap_or("???")),
+            ),
+        }
+        if let Some(build_id) = frame.build_id {
+            spans.push(
+                self.theme
+                    .suppressed
+                    .apply(format!("  (BuildId: {build_id})")),
+            );
+        }
+
+        Self::spans_to_log_line(spans)
     }
 
-    fn format_datetime(datetime: &UtcDateTime) -> ColoredString {
-        datetime
-            .format(DATETIME_FORMAT)
-            .unwrap_or_else(|e| panic!("failed to format datetime: {e}"))
-            .color(colors::DATETIME)
+    /// Formats a `Caused by:` or `Suppressed:` line as a branch of the tree of exceptions, at the given depth.
+    fn format_stacktrace_cause(&self, entry: &LogEntry, exception: Exception, depth: usize) -> FormattedLogLine {
+        let style = self
+            .theme
+            .level(entry.level)
+            .message
+            .overlay(self.theme.stacktrace_cause);
+        let mut spans = vec![
+            ColoredString::from(" ".repeat(self.tree_column(depth.saturating_sub(1)))),
+            style.apply(TREE_BRANCH),
+            style.apply(exception.relation.label().unwrap_or_default()),
+            style.overlay(self.theme.exception).apply(exception.class),
+        ];
+        if let Some(message) = exception.message {
+            spans.push(style.apply(format!(": {message}")));
+        }
+
+        Self::spans_to_log_line(spans)
+    }
+
+    /// Returns the column at which exceptions at `depth` in the tree of exceptions start.
+    const fn tree_column(&self, depth: usize) -> usize {
+        self.message_column() + depth * TREE_BRANCH_WIDTH
+    }
+
+    const fn message_column(&self) -> usize {
+        self.message_column.get()
     }
 
-    fn format_pid(pid: u32) -> ColoredString {
-        format!("{pid:>5}").color(colors::PID)
+    fn format_regular_entry(&self, entry: &LogEntry, intervals: Intervals) -> FormattedLogLine {
+        let mut spans = Vec::new();
+        for item in self.template.items() {
+            match item {
+                TemplateItem::Literal(text) => spans.push(ColoredString::from(text.as_str())),
+                TemplateItem::Field {
+                    field: TemplateField::Message,
+                    ..
+                } => {
+                    let column = spans.iter().map(|span| wrap::display_width(span)).sum();
+                    self.message_column.set(column);
+                    spans.extend(self.format_message(entry));
+                },
+                TemplateItem::Field {
+                    field: TemplateField::Time,
+                    time_format,
+                    ..
+                } => spans.extend(self.format_time(entry, intervals, time_format.as_ref())),
+                TemplateItem::Field { field, width, .. } => {
+                    spans.push(self.format_field(entry, intervals, *field, *width));
+                },
+            }
+        }
+
+        Self::spans_to_log_line(spans)
     }
 
-    fn format_tid(tid: u32) -> ColoredString {
-        format!("{tid:>5}").color(colors::TID)
+    fn spans_to_log_line(spans: Vec<ColoredString>) -> FormattedLogLine {
+        let spans = NEVec::try_from_vec(spans).expect("formatted lines have at least one span");
+        FormattedLogLine::new(nev![FormattedPart::new(spans)])
     }
 
-    fn format_log_level(level: LogLevel) -> ColoredString {
-        format!(" {level} ")
-            .color(colors::levels::FOREGROUND)
-            .on_color(Self::color_of_level(level))
-            .bold()
+    fn format_field(
+        &self,
+        entry: &LogEntry,
+        intervals: Intervals,
+        field: TemplateField,
+        width: FieldWidth,
+    ) -> ColoredString {
+        match field {
+            TemplateField::Level => self
+                .theme
+                .level(entry.level)
+                .badge
+                .apply(Self::fit(&entry.level.to_string(), width)),
+            TemplateField::Tag => self.format_tag(&entry.tag, width),
+            TemplateField::Pid => self.format_pid(entry.pid, width),
+            TemplateField::Tid => self.format_tid(entry.tid, width),
+            TemplateField::Time | TemplateField::Message => {
+                unreachable!("times and messages are formatted as several spans")
+            },
+            // Process names are styled like tags, as both name what logged the entry.
+            TemplateField::Process => {
+                let process = self
+                    .process_names
+                    .as_ref()
+                    .and_then(|process_names| process_names.get(entry.pid))
+                    .unwrap_or_else(|| "?".to_owned());
+                self.theme.tag.apply(Self::fit(&process, width))
+            },
+            TemplateField::Device => self
+                .theme
+                .header
+                .apply(Self::fit(self.device.as_deref().unwrap_or("?"), width)),
+            TemplateField::Source => self.theme.header.apply(Self::fit(&self.source, width)),
+            TemplateField::Delta => {
+                let delta = format!("{:+.3}", intervals.since_previous.as_seconds_f64());
+                self.delta_style(intervals).apply(Self::fit(&delta, width))
+            },
+        }
     }
 
-    fn format_tag(&self, tag: &str) -> ColoredString {
-        if tag.len() > self.tag_length.get() {
-            self.tag_length.set(tag.len().min(MAX_TAG_LENGTH));
+    fn fit(text: &str, width: FieldWidth) -> String {
+        match width {
+            FieldWidth::Natural | FieldWidth::TagColumn => text.to_owned(),
+            FieldWidth::Fixed(width, align) => layout::fit(text, width, align),
         }
+    }
+
+    fn format_time(
+        &self,
+        entry: &LogEntry,
+        intervals: Intervals,
+        format: Option<&OwnedFormatItem>,
+    ) -> Vec<ColoredString> {
+        let since_first = || {
+            self.theme
+                .datetime
+                .apply(timing::format_interval(intervals.since_first, false))
+        };
+        let since_previous = || {
+            self.delta_style(intervals)
+                .apply(timing::format_interval(intervals.since_previous, true))
+        };
 
-        let padded_tag = format!("{tag:^width$}", width = self.tag_length.get());
+        match self.time_mode {
+            TimeMode::Absolute => vec![self.format_datetime(entry.datetime, format)],
+            TimeMode::Relative => vec![since_first()],
+            TimeMode::Delta => vec![since_previous()],
+            TimeMode::Elapsed => vec![since_first(), ColoredString::from(" "), since_previous()],
+        }
+    }
+
+    fn delta_style(&self, intervals: Intervals) -> Style {
+        if intervals.since_previous > self.slow_gap {
+            self.theme.gap
+        } else {
+            self.theme.datetime
+        }
+    }
+
+    fn format_datetime(&self, datetime: UtcDateTime, format: Option<&OwnedFormatItem>) -> ColoredString {
+        let datetime = format.map_or_else(
+            || self.datetime_to_string(datetime),
+            |format| {
+                self.display_zone
+                    .localize(datetime)
+                    .format(format)
+                    .unwrap_or_else(|e| panic!("failed to format datetime: {e}"))
+            },
+        );
+        self.theme.datetime.apply(datetime)
+    }
+
+    fn datetime_to_string(&self, datetime: UtcDateTime) -> String {
+        self.display_zone
+            .localize(datetime)
+            .format(DATETIME_FORMAT)
+            .unwrap_or_else(|e| panic!("failed to format datetime: {e}"))
+    }
+
+    fn format_pid(&self, pid: u32, width: FieldWidth) -> ColoredString {
+        let pid = pid.to_string();
+        let style = self.value_style(ColorBy::Pid, &pid, self.theme.pid);
+        self.color_unless_highlighted(&pid, &Self::fit(&pid, width), style)
+    }
+
+    fn format_tid(&self, tid: u32, width: FieldWidth) -> ColoredString {
+        let tid = tid.to_string();
+        self.value_style(ColorBy::Tid, &tid, self.theme.tid)
+            .apply(Self::fit(&tid, width))
+    }
+
+    fn format_tag(&self, tag: &str, width: FieldWidth) -> ColoredString {
+        let padded_tag = if width == FieldWidth::TagColumn {
+            if self.tag_width == TagWidth::Auto {
+                let length = wrap::display_width(tag).min(MAX_AUTO_TAG_WIDTH);
+                self.tag_length.set(self.tag_length.get().max(length));
+            }
+            layout::fit(tag, self.tag_length.get(), self.tag_align)
+        } else {
+            Self::fit(tag, width)
+        };
 
         match tag {
-            MISSING_TAG => padded_tag.color(colors::MISSING_TAG).italic(),
-            _ => padded_tag.color(colors::TAG),
+            MISSING_TAG => self.theme.missing_tag.apply(padded_tag),
+            _ => self.color_unless_highlighted(tag, &padded_tag, self.value_style(ColorBy::Tag, tag, self.theme.tag)),
         }
     }
 
-    fn format_message(message: &str, level: LogLevel) -> ColoredString {
-        message.color(Self::color_of_level(level))
+    fn format_message(&self, entry: &LogEntry) -> Vec<ColoredString> {
+        let message = entry.message.as_str();
+        let mut message_style = self.theme.level(entry.level).message;
+        // The signal and abort message are the most telling lines of a native crash.
+        if let Some(TombstoneLine::Signal(_) | TombstoneLine::AbortMessage(_)) = TombstoneLine::parse(entry) {
+            message_style = message_style.overlay(self.theme.crash);
+        }
+        let mut spans = Vec::new();
+        let mut position = 0;
+
+        for (range, style) in self.styled_ranges(message) {
+            if position < range.start {
+                spans.push(message_style.apply(&message[position..range.start]));
+            }
+            let text = &message[range.clone()];
+            spans.push(match style {
+                MessageStyle::Highlight(highlight_color) => self.highlight(text, highlight_color).underline(),
+                MessageStyle::Exception => message_style.overlay(self.theme.exception).apply(text),
+                MessageStyle::Field(FieldSpan::Key) => self.theme.field_key.apply(text),
+                MessageStyle::Field(FieldSpan::Value) => self.theme.field_value.apply(text),
+            });
+            position = range.end;
+        }
+        if position < message.len() || spans.is_empty() {
+            spans.push(message_style.apply(&message[position..]));
+        }
+
+        spans
     }
 
-    const fn color_of_level(level: LogLevel) -> Color {
-        match level {
-            LogLevel::Silent => colors::levels::SILENT,
-            LogLevel::Verbose => colors::levels::VERBOSE,
-            LogLevel::Debug => colors::levels::DEBUG,
-            LogLevel::Info => colors::levels::INFO,
-            LogLevel::Warning => colors::levels::WARNING,
-            LogLevel::Error => colors::levels::ERROR,
-            LogLevel::Fatal => colors::levels::FATAL,
+    /// Returns the non-overlapping ranges of `message` that are styled differently than the rest of it, sorted by
+    /// position. Highlights take precedence over the class of a thrown exception, which takes precedence over fields.
+    fn styled_ranges(&self, message: &str) -> Vec<(Range<usize>, MessageStyle)> {
+        let mut ranges = highlighted_ranges(&self.highlights, message)
+            .into_iter()
+            .map(|(range, color)| (range, MessageStyle::Highlight(color)))
+            .collect::<Vec<_>>();
+
+        let mut candidates = Vec::new();
+        if let Some(StacktraceLine::Exception(Exception {
+            relation: Relation::Thrown,
+            class,
+            ..
+        })) = StacktraceLine::parse(message)
+        {
+            candidates.push((0..class.len(), MessageStyle::Exception));
+        }
+        if self.color_fields {
+            let fields = Fields::extract(message)
+                .spans
+                .into_iter()
+                .map(|(range, span)| (range, MessageStyle::Field(span)));
+            candidates.extend(fields);
         }
+
+        for (range, style) in candidates {
+            if ranges
+                .iter()
+                .all(|(taken, _)| range.end <= taken.start || taken.end <= range.start)
+            {
+                ranges.push((range, style));
+            }
+        }
+        ranges.sort_by_key(|(range, _)| range.start);
+
+        ranges
+    }
+
+    /// Returns `style` with its foreground replaced by the pinned or hashed color of `value`, if it has one.
+    fn value_style(&self, element: ColorBy, value: &str, style: Style) -> Style {
+        let pinned = match element {
+            ColorBy::Tag => self.theme.tag_colors.get(value).copied(),
+            ColorBy::Pid | ColorBy::Tid => None,
+        };
+        let color = pinned.or_else(|| {
+            self.color_by
+                .contains(&element)
+                .then(|| palette::hashed_color(value, self.color_depth))
+        });
+
+        color.map_or(style, |color| style.overlay(Style::fg(color)))
+    }
+
+    /// Styles `padded` with `style`, or highlights it if a highlight rule matches the entire `value`.
+    fn color_unless_highlighted(&self, value: &str, padded: &str, style: Style) -> ColoredString {
+        self.highlights
+            .iter()
+            .find(|highlight| highlight.matches_whole(value))
+            .map_or_else(
+                || style.apply(padded),
+                |highlight| self.highlight(padded, highlight.color),
+            )
+    }
+
+    fn highlight(&self, text: &str, color: Option<Color>) -> ColoredString {
+        let style = color.map_or(self.theme.highlight, |color| {
+            self.theme
+                .highlight
+                .on(palette::degrade(color, self.color_depth))
+        });
+        style.apply(text)
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+enum MessageStyle {
+    Highlight(Option<Color>),
+    Exception,
+    Field(FieldSpan),
+}
+
 #[extend::ext]
 impl ColoredString {
     fn to_log_line(self) -> FormattedLogLine {
-        FormattedLogLine::new(nev![self])
+        FormattedLogLine::new(nev![self.into()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nest_causes_of_each_thread_separately() {
+        let formatter = LogcatFormatter::new();
+        let format = |tid: u32, message: &str| {
+            let entry = LogEntry::test("AndroidRuntime", message).with_tid(tid);
+            formatter.format_log_entry(&entry).to_string()
+        };
+
+        format(1, "java.lang.IllegalStateException: first thread");
+        let first_cause = format(1, "Caused by: java.io.IOException");
+        format(2, "java.lang.RuntimeException: second thread");
+        let second_cause = format(1, "Caused by: java.net.SocketException");
+
+        let branch_column = |line: &str| line.find(TREE_BRANCH).unwrap();
+        assert_eq!(
+            branch_column(&second_cause),
+            branch_column(&first_cause) + TREE_BRANCH_WIDTH
+        );
+    }
+
+    #[test]
+    fn test_start_new_exceptions_of_a_thread_at_depth_0() {
+        let formatter = LogcatFormatter::new();
+        let format = |message: &str| {
+            formatter
+                .format_log_entry(&LogEntry::test("AndroidRuntime", message))
+                .to_string()
+        };
+
+        format("java.lang.IllegalStateException: first");
+        let first_cause = format("Caused by: java.io.IOException");
+        format("Caused by: java.net.SocketException");
+        format("Shutting down VM");
+        assert!(formatter.exception_trees.borrow().is_empty());
+
+        format("java.lang.IllegalStateException: second");
+        let second_cause = format("Caused by: java.io.IOException");
+        let branch_column = |line: &str| line.find(TREE_BRANCH).unwrap();
+        assert_eq!(branch_column(&second_cause), branch_column(&first_cause));
     }
 }
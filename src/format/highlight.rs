@@ -0,0 +1,101 @@
+use std::{ops::Range, str::FromStr};
+
+use anyhow::anyhow;
+use colored::Color;
+use regex::Regex;
+
+use crate::prelude::*;
+
+/// A rule that makes text matching a regex stand out, without affecting filtering.
+pub struct Highlight {
+    regex: Regex,
+    /// The background color of highlighted text, or `None` to use the theme's highlight style.
+    pub color: Option<Color>,
+}
+
+impl Highlight {
+    /// Parses a rule of the form `REGEX[=COLOR]`.
+    ///
+    /// The color suffix is only split off when it names a valid color, so regexes containing `=` can still be used.
+    pub fn new(rule: &str) -> Result<Self> {
+        let (regex, color) = match rule.rsplit_once('=') {
+            Some((regex, color)) if !regex.is_empty() => match Color::from_str(color) {
+                Ok(color) => (regex, Some(color)),
+                Err(()) => (rule, None),
+            },
+            _ => (rule, None),
+        };
+
+        let regex = Regex::new(regex).map_err(|e| anyhow!("failed to compile highlight regex: {e}"))?;
+        Ok(Self { regex, color })
+    }
+
+    /// Returns `true` if the regex matches the entire `text`, the same way tag filters match tags.
+    pub fn matches_whole(&self, text: &str) -> bool {
+        self.regex
+            .find(text)
+            .is_some_and(|m| m.start() == 0 && m.end() == text.len())
+    }
+}
+
+/// Finds the non-overlapping ranges of `text` matched by `highlights`, sorted by position.
+///
+/// When matches of different rules overlap, the one starting first wins, and ties are broken by rule order.
+pub fn highlighted_ranges(highlights: &[Highlight], text: &str) -> Vec<(Range<usize>, Option<Color>)> {
+    let mut matches = highlights
+        .iter()
+        .enumerate()
+        .flat_map(|(index, highlight)| {
+            highlight
+                .regex
+                .find_iter(text)
+                .filter(|m| !m.is_empty())
+                .map(move |m| (m.range(), index, highlight.color))
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|(range, index, _)| (range.start, *index));
+
+    let mut ranges = Vec::<(Range<usize>, Option<Color>)>::new();
+    for (range, _, color) in matches {
+        if ranges
+            .last()
+            .is_none_or(|(last, _)| last.end <= range.start)
+        {
+            ranges.push((range, color));
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_highlight_rule() {
+        let highlight = Highlight::new("uid").unwrap();
+        assert_eq!(highlight.color, None);
+
+        let highlight = Highlight::new("uid=red").unwrap();
+        assert_eq!(highlight.color, Some(Color::Red));
+        assert!(highlight.matches_whole("uid"));
+
+        let highlight = Highlight::new("uid = \\d+").unwrap();
+        assert_eq!(highlight.color, None);
+        assert!(highlight.matches_whole("uid = 10147"));
+
+        assert!(Highlight::new("(unclosed=blue").is_err());
+    }
+
+    #[test]
+    fn test_highlighted_ranges() {
+        let highlights = vec![
+            Highlight::new("uid=red").unwrap(),
+            Highlight::new("State uid=blue").unwrap(),
+            Highlight::new("\\d+").unwrap(),
+        ];
+        let ranges = highlighted_ranges(&highlights, "removeUidState uid = 10147");
+
+        assert_eq!(ranges, vec![(9..18, Some(Color::Blue)), (21..26, None)]);
+    }
+}
@@ -0,0 +1,108 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use strum::{Display, EnumString};
+
+use super::wrap::{char_width, display_width};
+use crate::prelude::*;
+
+/// The longest tag `auto` widths grow to, which is the longest tag older Android versions allowed.
+pub const MAX_AUTO_TAG_WIDTH: usize = 23;
+
+const ELLIPSIS: char = '…';
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum TagWidth {
+    /// Grow the column to the longest tag seen so far, up to [`MAX_AUTO_TAG_WIDTH`].
+    #[default]
+    Auto,
+    Fixed(usize),
+}
+
+impl FromStr for TagWidth {
+    type Err = anyhow::Error;
+
+    fn from_str(width: &str) -> Result<Self> {
+        if width == "auto" {
+            return Ok(Self::Auto);
+        }
+
+        match width.parse() {
+            Ok(0) => Err(anyhow!("the tag width must be at least 1")),
+            Ok(width) => Ok(Self::Fixed(width)),
+            Err(e) => Err(anyhow!("invalid tag width `{width}`, expected a number or `auto`: {e}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum TagAlign {
+    #[default]
+    Left,
+    Right,
+    Center,
+}
+
+/// Fits `text` into a column of `width` terminal columns, truncating it with an ellipsis if it's wider.
+pub fn fit(text: &str, width: usize, align: TagAlign) -> String {
+    let text_width = display_width(text);
+    if text_width > width {
+        let mut truncated = String::with_capacity(text.len());
+        let mut column = 0;
+        for char in text.chars() {
+            let char_width = char_width(char);
+            if column + char_width >= width {
+                break;
+            }
+            truncated.push(char);
+            column += char_width;
+        }
+        truncated.push(ELLIPSIS);
+        // A wide character that doesn't fit before the ellipsis leaves a column to pad.
+        truncated.push_str(&" ".repeat(width.saturating_sub(column + 1)));
+        return truncated;
+    }
+
+    let padding = width - text_width;
+    let (left, right) = match align {
+        TagAlign::Left => (0, padding),
+        TagAlign::Right => (padding, 0),
+        TagAlign::Center => (padding / 2, padding - padding / 2),
+    };
+    format!("{}{text}{}", " ".repeat(left), " ".repeat(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tag_width() {
+        assert_eq!("auto".parse::<TagWidth>().unwrap(), TagWidth::Auto);
+        assert_eq!("12".parse::<TagWidth>().unwrap(), TagWidth::Fixed(12));
+        assert!("0".parse::<TagWidth>().is_err());
+        assert!("wide".parse::<TagWidth>().is_err());
+    }
+
+    #[test]
+    fn test_fit_tag() {
+        assert_eq!(fit("Finsky", 8, TagAlign::Left), "Finsky  ");
+        assert_eq!(fit("Finsky", 8, TagAlign::Right), "  Finsky");
+        assert_eq!(fit("Finsky", 8, TagAlign::Center), " Finsky ");
+        assert_eq!(fit("Finsky", 6, TagAlign::Right), "Finsky");
+        assert_eq!(fit("ActivityManager", 8, TagAlign::Left), "Activit…");
+        assert_eq!(fit("Größenänderung", 5, TagAlign::Left), "Größ…");
+    }
+
+    #[test]
+    fn test_fit_wide_and_combining_tags() {
+        assert_eq!(fit("日本語", 8, TagAlign::Left), "日本語  ");
+        assert_eq!(fit("日本語", 8, TagAlign::Right), "  日本語");
+        assert_eq!(fit("日本語", 7, TagAlign::Center), "日本語 ");
+        assert_eq!(fit("日本語", 5, TagAlign::Left), "日本…");
+        assert_eq!(fit("日本語", 4, TagAlign::Left), "日… ");
+        assert_eq!(fit("Cafe\u{301}", 6, TagAlign::Right), "  Cafe\u{301}");
+        assert_eq!(fit("Cafe\u{301}Bar", 5, TagAlign::Left), "Cafe\u{301}…");
+    }
+}
@@ -1,18 +1,47 @@
 use colored::ColoredString;
-use nonempty_collections::NEVec;
+use nonempty_collections::{NEVec, nev};
+
+use super::wrap::LineWrap;
 
 pub struct FormattedLogLine {
-    parts: NEVec<ColoredString>,
+    parts: NEVec<FormattedPart>,
+    wrap: Option<LineWrap>,
+    /// A line printed before this one, such as a gap marker.
+    marker: Option<ColoredString>,
 }
 
 impl FormattedLogLine {
-    pub const fn new(parts: NEVec<ColoredString>) -> Self {
-        Self { parts }
+    pub const fn new(parts: NEVec<FormattedPart>) -> Self {
+        Self {
+            parts,
+            wrap: None,
+            marker: None,
+        }
     }
-}
 
-impl std::fmt::Display for FormattedLogLine {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Fits the line into the terminal when it's displayed.
+    pub const fn set_wrap(&mut self, wrap: LineWrap) {
+        self.wrap = Some(wrap);
+    }
+
+    pub fn set_marker(&mut self, marker: ColoredString) {
+        self.marker = Some(marker);
+    }
+
+    pub fn push(&mut self, part: FormattedPart) {
+        self.parts.push(part);
+    }
+
+    pub const fn marker(&self) -> Option<&ColoredString> {
+        self.marker.as_ref()
+    }
+
+    /// Returns the columns of the line, which are separated by a space.
+    pub fn parts(&self) -> impl Iterator<Item = &FormattedPart> {
+        self.parts.iter()
+    }
+
+    fn write_parts(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
         write!(f, "{}", self.parts.first())?;
         for part in self.parts.iter().skip(1) {
             write!(f, " {part}")?;
@@ -20,3 +49,49 @@ impl std::fmt::Display for FormattedLogLine {
         Ok(())
     }
 }
+
+impl std::fmt::Display for FormattedLogLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(marker) = &self.marker {
+            writeln!(f, "{marker}")?;
+        }
+
+        let Some(wrap) = self.wrap else {
+            return self.write_parts(f);
+        };
+
+        let mut line = String::new();
+        self.write_parts(&mut line)?;
+        write!(f, "{}", wrap.apply(&line))
+    }
+}
+
+/// A single column of a formatted log line, made of one or more adjacent colored spans.
+pub struct FormattedPart {
+    spans: NEVec<ColoredString>,
+}
+
+impl FormattedPart {
+    pub const fn new(spans: NEVec<ColoredString>) -> Self {
+        Self { spans }
+    }
+
+    pub fn spans(&self) -> impl Iterator<Item = &ColoredString> {
+        self.spans.iter()
+    }
+}
+
+impl From<ColoredString> for FormattedPart {
+    fn from(span: ColoredString) -> Self {
+        Self::new(nev![span])
+    }
+}
+
+impl std::fmt::Display for FormattedPart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for span in &self.spans {
+            write!(f, "{span}")?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,19 @@
-mod colors;
 mod formatter;
+mod highlight;
+mod layout;
 mod log_line;
+mod palette;
+mod template;
+mod theme;
+mod timing;
+mod wrap;
 
 pub use formatter::LogcatFormatter;
+pub use highlight::Highlight;
+pub use layout::{TagAlign, TagWidth};
 pub use log_line::FormattedLogLine;
+pub use palette::{ColorBy, ColorDepth, ColorMode, rgb};
+pub use template::{DEFAULT_PRESET, Template, TemplateField};
+pub use theme::{Style, Theme};
+pub use timing::{DeltaBy, TimeMode};
+pub use wrap::WrapMode;
@@ -0,0 +1,290 @@
+use std::env;
+
+use colored::Color;
+use strum::{Display, EnumString};
+
+/// The elements of a log line that can be colored by their value.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum ColorBy {
+    Tag,
+    Pid,
+    Tid,
+}
+
+/// When to color the output.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum ColorMode {
+    /// Color the output if stdout is a terminal, unless overridden by `NO_COLOR` or `CLICOLOR_FORCE`.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn is_enabled(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+                    false
+                } else if env::var("CLICOLOR_FORCE").is_ok_and(|value| !value.is_empty() && value != "0") {
+                    true
+                } else {
+                    atty::is(atty::Stream::Stdout) && !env::var("TERM").is_ok_and(|term| term == "dumb")
+                }
+            },
+        }
+    }
+}
+
+/// How many colors the terminal can display.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Display, EnumString)]
+pub enum ColorDepth {
+    #[default]
+    #[strum(serialize = "16")]
+    Ansi16,
+    #[strum(serialize = "256")]
+    Ansi256,
+    #[strum(serialize = "truecolor")]
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Guesses the color depth of the terminal from the `COLORTERM` and `TERM` environment variables.
+    pub fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+
+        if matches!(colorterm.as_str(), "truecolor" | "24bit") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+}
+
+/// Colors readable on both dark and light backgrounds. Black, white and grays are left out as they are likely to
+/// blend with the background or with the other elements of the line.
+const ANSI_16_PALETTE: [Color; 12] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightYellow,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+];
+
+/// The colors of 16 color terminals, in the order of their codes, with the RGB values used by xterm.
+const ANSI_16_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::White, (229, 229, 229)),
+    (Color::BrightBlack, (127, 127, 127)),
+    (Color::BrightRed, (255, 0, 0)),
+    (Color::BrightGreen, (0, 255, 0)),
+    (Color::BrightYellow, (255, 255, 0)),
+    (Color::BrightBlue, (92, 92, 255)),
+    (Color::BrightMagenta, (255, 0, 255)),
+    (Color::BrightCyan, (0, 255, 255)),
+    (Color::BrightWhite, (255, 255, 255)),
+];
+
+/// The intensities of each channel in the 6×6×6 cube of 256 color terminals.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Mid-intensity colors of the 6×6×6 cube of 256 color terminals.
+const ANSI_256_PALETTE: [u8; 30] = [
+    33, 38, 39, 43, 44, 69, 74, 75, 79, 80, 99, 105, 111, 117, 141, 147, 160, 166, 167, 168, 169, 170, 172, 173, 174,
+    178, 179, 184, 185, 209,
+];
+
+/// Returns the color of `value`, derived from a hash that is stable across runs and machines.
+pub fn hashed_color(value: &str, depth: ColorDepth) -> Color {
+    let hash = fnv1a(value.as_bytes());
+
+    match depth {
+        ColorDepth::Ansi16 => ANSI_16_PALETTE[index(hash, ANSI_16_PALETTE.len())],
+        ColorDepth::Ansi256 => Color::AnsiColor(ANSI_256_PALETTE[index(hash, ANSI_256_PALETTE.len())]),
+        ColorDepth::TrueColor => {
+            #[allow(clippy::cast_precision_loss)]
+            let hue = (hash % 360) as f64;
+            hsl_to_color(hue, 0.65, 0.6)
+        },
+    }
+}
+
+/// Returns the closest color to `color` that a terminal with the given depth can display.
+pub fn degrade(color: Color, depth: ColorDepth) -> Color {
+    match (color, depth) {
+        (Color::TrueColor { r, g, b }, ColorDepth::Ansi256) => Color::AnsiColor(rgb_to_ansi_256((r, g, b))),
+        (Color::TrueColor { r, g, b }, ColorDepth::Ansi16) => closest_ansi_16((r, g, b)),
+        (Color::AnsiColor(code), ColorDepth::Ansi16) => closest_ansi_16(ansi_256_to_rgb(code)),
+        _ => color,
+    }
+}
+
+/// Returns the RGB value of `color`, using the values of xterm for the colors of 16 and 256 color terminals.
+pub fn rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::TrueColor { r, g, b } => (r, g, b),
+        Color::AnsiColor(code) => ansi_256_to_rgb(code),
+        named => ANSI_16_COLORS
+            .iter()
+            .find(|(candidate, _)| *candidate == named)
+            .map(|(_, rgb)| *rgb)
+            .expect("named colors are in the palette"),
+    }
+}
+
+fn rgb_to_ansi_256(rgb: (u8, u8, u8)) -> u8 {
+    let cube_index = |value: u8| match value {
+        0..48 => 0,
+        48..115 => 1,
+        _ => (value - 35) / 40,
+    };
+    let (r, g, b) = (cube_index(rgb.0), cube_index(rgb.1), cube_index(rgb.2));
+    let cube = 16 + 36 * r + 6 * g + b;
+
+    #[allow(clippy::cast_possible_truncation)]
+    let average = ((u16::from(rgb.0) + u16::from(rgb.1) + u16::from(rgb.2)) / 3) as u8;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23);
+
+    if distance(rgb, ansi_256_to_rgb(gray)) < distance(rgb, ansi_256_to_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+fn ansi_256_to_rgb(code: u8) -> (u8, u8, u8) {
+    match code {
+        0..16 => ANSI_16_COLORS[usize::from(code)].1,
+        16..232 => {
+            let index = code - 16;
+            let level = |value: u8| CUBE_LEVELS[usize::from(value)];
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        },
+        _ => {
+            let gray = 8 + 10 * (code - 232);
+            (gray, gray, gray)
+        },
+    }
+}
+
+fn closest_ansi_16(rgb: (u8, u8, u8)) -> Color {
+    ANSI_16_COLORS
+        .iter()
+        .min_by_key(|(_, candidate)| distance(rgb, *candidate))
+        .map(|(color, _)| *color)
+        .expect("the palette is not empty")
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let channel = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+    channel(a.0, b.0) + channel(a.1, b.1) + channel(a.2, b.2)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn index(hash: u64, len: usize) -> usize {
+    (hash % len as u64) as usize
+}
+
+/// The 64 bit FNV-1a hash, used instead of `std`'s hashers whose output may change between Rust versions.
+const fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn hsl_to_color(hue: f64, saturation: f64, lightness: f64) -> Color {
+    let chroma = (1.0 - 2.0f64.mul_add(lightness, -1.0).abs()) * saturation;
+    let second = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let lightening = lightness - chroma / 2.0;
+    let (red, green, blue) = match hue {
+        hue if hue < 60.0 => (chroma, second, 0.0),
+        hue if hue < 120.0 => (second, chroma, 0.0),
+        hue if hue < 180.0 => (0.0, chroma, second),
+        hue if hue < 240.0 => (0.0, second, chroma),
+        hue if hue < 300.0 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+    let channel = |value: f64| ((value + lightening) * 255.0).round() as u8;
+
+    Color::TrueColor {
+        r: channel(red),
+        g: channel(green),
+        b: channel(blue),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashed_colors_are_stable() {
+        assert_eq!(fnv1a(b"Finsky"), 0x565a_db73_4433_917b);
+        assert_eq!(
+            hashed_color("Finsky", ColorDepth::Ansi16),
+            hashed_color("Finsky", ColorDepth::Ansi16)
+        );
+        assert!(ANSI_16_PALETTE.contains(&hashed_color("ActivityManager", ColorDepth::Ansi16)));
+        assert!(matches!(
+            hashed_color("ActivityManager", ColorDepth::Ansi256),
+            Color::AnsiColor(code) if ANSI_256_PALETTE.contains(&code)
+        ));
+        assert!(matches!(
+            hashed_color("ActivityManager", ColorDepth::TrueColor),
+            Color::TrueColor { .. }
+        ));
+    }
+
+    #[test]
+    fn test_degrade_colors() {
+        let orange = Color::TrueColor { r: 255, g: 136, b: 0 };
+
+        assert_eq!(degrade(orange, ColorDepth::TrueColor), orange);
+        assert_eq!(degrade(orange, ColorDepth::Ansi256), Color::AnsiColor(208));
+        assert_eq!(degrade(orange, ColorDepth::Ansi16), Color::Yellow);
+        assert_eq!(degrade(Color::AnsiColor(196), ColorDepth::Ansi16), Color::BrightRed);
+        assert_eq!(degrade(Color::AnsiColor(244), ColorDepth::Ansi16), Color::BrightBlack);
+        assert_eq!(degrade(Color::Cyan, ColorDepth::Ansi16), Color::Cyan);
+        assert_eq!(
+            degrade(Color::TrueColor { r: 128, g: 128, b: 128 }, ColorDepth::Ansi256),
+            Color::AnsiColor(244)
+        );
+    }
+
+    #[test]
+    fn test_hsl_to_color() {
+        assert_eq!(hsl_to_color(0.0, 1.0, 0.5), Color::TrueColor { r: 255, g: 0, b: 0 });
+        assert_eq!(hsl_to_color(120.0, 1.0, 0.5), Color::TrueColor { r: 0, g: 255, b: 0 });
+        assert_eq!(
+            hsl_to_color(210.0, 0.5, 0.5),
+            Color::TrueColor { r: 64, g: 128, b: 191 }
+        );
+    }
+}
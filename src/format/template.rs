@@ -0,0 +1,329 @@
+use anyhow::anyhow;
+use chumsky::prelude::*;
+use strum::{Display, EnumString};
+use time::format_description::{self, OwnedFormatItem};
+
+use super::layout::TagAlign;
+use crate::prelude::*;
+
+type ParserErr<'src> = extra::Err<Rich<'src, char>>;
+
+/// Templates named after the formats of `logcat -v`.
+pub const PRESETS: [(&str, &str); 7] = [
+    ("threadtime", "{time} {pid:>5} {tid:>5} {level:^3} {tag:auto} {msg}"),
+    ("brief", "{level}/{tag}({pid:>5}): {msg}"),
+    ("tag", "{level}/{tag}: {msg}"),
+    ("process", "{level}({pid:>5}) {msg}  ({tag})"),
+    ("thread", "{level}({pid:>5}:{tid:>5}) {msg}"),
+    ("time", "{time} {level}/{tag}({pid:>5}): {msg}"),
+    ("raw", "{msg}"),
+];
+
+pub const DEFAULT_PRESET: &str = PRESETS[0].0;
+
+const DEFAULT_TIME_FORMAT: &str = "%m-%d %H:%M:%S%.3f";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum TemplateField {
+    Time,
+    Level,
+    Tag,
+    Pid,
+    Tid,
+    #[strum(serialize = "msg", serialize = "message")]
+    Message,
+    /// The name of the process that logged the entry, when known.
+    Process,
+    /// The serial of the device the entries come from.
+    Device,
+    /// The file or command the entries are read from.
+    Source,
+    /// The time elapsed since the previous entry.
+    Delta,
+}
+
+/// How much room a field takes in the output.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FieldWidth {
+    /// As much as its value needs.
+    Natural,
+    /// The tag column laid out with `--tag-width` and `--tag-align`.
+    TagColumn,
+    Fixed(usize, TagAlign),
+}
+
+#[derive(Debug)]
+pub enum TemplateItem {
+    Literal(String),
+    Field {
+        field: TemplateField,
+        width: FieldWidth,
+        /// The format of `{time}` fields.
+        time_format: Option<OwnedFormatItem>,
+    },
+}
+
+/// The layout of regular log entries, e.g. `{time:%H:%M:%S} {level} {tag:20} {msg}`.
+///
+/// Fields are written in braces, optionally followed by a colon and a spec. The spec of `{time}` is a `strftime`-like
+/// format, and the spec of other fields is a width, optionally preceded by an alignment: `<`, `>` or `^`. Literal
+/// braces are written `{{` and `}}`.
+#[derive(Debug)]
+pub struct Template {
+    items: Vec<TemplateItem>,
+}
+
+impl Template {
+    /// Parses a template, or looks up a preset if `template` contains no fields.
+    pub fn new(template: &str) -> Result<Self> {
+        if let Some((_, preset)) = PRESETS.iter().find(|(name, _)| *name == template) {
+            return Self::parse(preset);
+        }
+        if !template.contains('{') {
+            return Err(anyhow!(
+                "unknown format `{template}`, expected a template or one of: {}",
+                PRESETS.map(|(name, _)| name).join(", ")
+            ));
+        }
+        Self::parse(template)
+    }
+
+    fn parse(template: &str) -> Result<Self> {
+        let raw_items = template_parser()
+            .parse(template)
+            .into_result()
+            .map_err(|errors| {
+                anyhow!(
+                    "failed to parse format template `{template}`: {}",
+                    errors
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                )
+            })?;
+
+        let items = raw_items
+            .into_iter()
+            .map(|item| match item {
+                RawItem::Literal(text) => Ok(TemplateItem::Literal(text)),
+                RawItem::Field(name, spec) => Self::parse_field(name, spec),
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { items })
+    }
+
+    fn parse_field(name: &str, spec: Option<&str>) -> Result<TemplateItem> {
+        let field = name
+            .parse()
+            .map_err(|_| anyhow!("unknown format field `{{{name}}}`"))?;
+
+        if field == TemplateField::Time {
+            let time_format = strftime_to_format_description(spec.unwrap_or(DEFAULT_TIME_FORMAT))?;
+            return Ok(TemplateItem::Field {
+                field,
+                width: FieldWidth::Natural,
+                time_format: Some(time_format),
+            });
+        }
+
+        Ok(TemplateItem::Field {
+            field,
+            width: Self::parse_width(field, spec.unwrap_or_default())?,
+            time_format: None,
+        })
+    }
+
+    fn parse_width(field: TemplateField, spec: &str) -> Result<FieldWidth> {
+        match spec {
+            "" => return Ok(FieldWidth::Natural),
+            "auto" if field == TemplateField::Tag => return Ok(FieldWidth::TagColumn),
+            _ => {},
+        }
+
+        let (align, width) = match spec.chars().next() {
+            Some('<') => (TagAlign::Left, &spec[1..]),
+            Some('>') => (TagAlign::Right, &spec[1..]),
+            Some('^') => (TagAlign::Center, &spec[1..]),
+            _ if matches!(field, TemplateField::Pid | TemplateField::Tid | TemplateField::Delta) => {
+                (TagAlign::Right, spec)
+            },
+            _ => (TagAlign::Left, spec),
+        };
+
+        match width.parse() {
+            Ok(0) | Err(_) => Err(anyhow!(
+                "invalid width `{spec}` of format field `{{{field}}}`, expected a positive number optionally preceded by \
+                 `<`, `>` or `^`"
+            )),
+            Ok(width) => Ok(FieldWidth::Fixed(width, align)),
+        }
+    }
+
+    pub fn items(&self) -> &[TemplateItem] {
+        &self.items
+    }
+
+    pub fn uses(&self, field: TemplateField) -> bool {
+        self.items
+            .iter()
+            .any(|item| matches!(item, TemplateItem::Field { field: used, .. } if *used == field))
+    }
+}
+
+enum RawItem<'src> {
+    Literal(String),
+    Field(&'src str, Option<&'src str>),
+}
+
+fn template_parser<'src>() -> impl Parser<'src, &'src str, Vec<RawItem<'src>>, ParserErr<'src>> {
+    let literal = choice((just("{{").to('{'), just("}}").to('}'), none_of("{}")))
+        .repeated()
+        .at_least(1)
+        .collect::<String>()
+        .map(RawItem::Literal);
+    let name = any()
+        .filter(|c: &char| c.is_ascii_alphabetic())
+        .repeated()
+        .at_least(1)
+        .to_slice();
+    let spec = just(':')
+        .ignore_then(none_of('}').repeated().to_slice())
+        .or_not();
+    let field = name
+        .then(spec)
+        .delimited_by(just('{'), just('}'))
+        .map(|(name, spec)| RawItem::Field(name, spec));
+
+    choice((literal, field))
+        .repeated()
+        .at_least(1)
+        .collect()
+        .then_ignore(end())
+}
+
+/// Converts a `strftime`-like format, e.g. `%H:%M:%S%.3f`, to a format description of the `time` crate.
+fn strftime_to_format_description(format: &str) -> Result<OwnedFormatItem> {
+    let mut description = String::new();
+    let mut chars = format.chars();
+
+    while let Some(char) = chars.next() {
+        if char == '[' {
+            description.push_str("[[");
+            continue;
+        }
+        if char != '%' {
+            description.push(char);
+            continue;
+        }
+
+        let component = match chars.next() {
+            Some('Y') => "[year]",
+            Some('y') => "[year repr:last_two]",
+            Some('m') => "[month]",
+            Some('b') => "[month repr:short]",
+            Some('d') => "[day]",
+            Some('j') => "[ordinal]",
+            Some('H') => "[hour]",
+            Some('I') => "[hour repr:12]",
+            Some('p') => "[period]",
+            Some('M') => "[minute]",
+            Some('S') => "[second]",
+            Some('f') => "[subsecond digits:9]",
+            Some('z') => "[offset_hour sign:mandatory][offset_minute]",
+            Some('.') => match (chars.next(), chars.next()) {
+                (Some(digits @ '1'..='9'), Some('f')) => {
+                    description.push_str(".[subsecond digits:");
+                    description.push(digits);
+                    description.push(']');
+                    continue;
+                },
+                _ => {
+                    return Err(anyhow!(
+                        "invalid time format `{format}`: expected `%.Nf` with N from 1 to 9"
+                    ));
+                },
+            },
+            Some('%') => "%",
+            Some(other) => return Err(anyhow!("invalid time format `{format}`: unknown specifier `%{other}`")),
+            None => return Err(anyhow!("invalid time format `{format}`: trailing `%`")),
+        };
+        description.push_str(component);
+    }
+
+    format_description::parse_owned::<1>(&description).map_err(|e| anyhow!("invalid time format `{format}`: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use time_macros::utc_datetime;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_templates() {
+        let template = Template::new("{time:%H:%M:%S%.3f} [{level}] {tag:20} {pid}/{tid:<6} {{{msg}}}").unwrap();
+        let fields = template
+            .items()
+            .iter()
+            .filter_map(|item| match item {
+                TemplateItem::Field { field, width, .. } => Some((*field, *width)),
+                TemplateItem::Literal(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            fields,
+            vec![
+                (TemplateField::Time, FieldWidth::Natural),
+                (TemplateField::Level, FieldWidth::Natural),
+                (TemplateField::Tag, FieldWidth::Fixed(20, TagAlign::Left)),
+                (TemplateField::Pid, FieldWidth::Natural),
+                (TemplateField::Tid, FieldWidth::Fixed(6, TagAlign::Left)),
+                (TemplateField::Message, FieldWidth::Natural),
+            ]
+        );
+        assert!(matches!(template.items().last(), Some(TemplateItem::Literal(text)) if text == "}"));
+        assert!(template.uses(TemplateField::Tid));
+        assert!(!template.uses(TemplateField::Process));
+
+        for (name, _) in PRESETS {
+            assert!(Template::new(name).is_ok(), "Expected preset to parse: {name}");
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_templates() {
+        let invalid_templates = vec![
+            "verbose",         // unknown preset
+            "{time} {lvl}",    // unknown field
+            "{tag:wide}",      // invalid width
+            "{pid:auto}",      // auto width of a field other than the tag
+            "{time:%Q}",       // unknown time specifier
+            "{time:%.f}",      // missing subsecond digits
+            "{msg",            // unclosed field
+            "{level} } {msg}", // unescaped brace
+        ];
+
+        for template in invalid_templates {
+            assert!(Template::new(template).is_err(), "Expected error for: {template}");
+        }
+    }
+
+    #[test]
+    fn test_strftime_formats() {
+        let datetime = utc_datetime!(2025-10-01 14:10:36.746);
+        let format = |format: &str| {
+            datetime
+                .format(&strftime_to_format_description(format).unwrap())
+                .unwrap()
+        };
+
+        assert_eq!(format(DEFAULT_TIME_FORMAT), "10-01 14:10:36.746");
+        assert_eq!(format("%Y-%m-%d [%I %p] %%"), "2025-10-01 [02 PM] %");
+        assert_eq!(format("%S%.1f"), "36.7");
+        assert_eq!(format("%H:%M %z"), "14:10 +0000");
+    }
+}
@@ -0,0 +1,639 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::anyhow;
+use colored::{Color, ColoredString, Colorize};
+use serde::{Deserialize, Deserializer, de::Error};
+use strum::IntoEnumIterator;
+
+use super::palette::{self, ColorDepth};
+use crate::{log::LogLevel, prelude::*};
+
+pub const BUILTIN_THEMES: [&str; 4] = ["dark", "light", "solarized", "high-contrast"];
+
+/// How a single element of the output is styled. Unset attributes are left as they are, so styles can be layered.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Style {
+    #[serde(deserialize_with = "deserialize_color")]
+    pub foreground: Option<Color>,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub background: Option<Color>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub dimmed: Option<bool>,
+}
+
+impl Style {
+    pub const fn fg(color: Color) -> Self {
+        Self {
+            foreground: Some(color),
+            background: None,
+            bold: None,
+            italic: None,
+            dimmed: None,
+        }
+    }
+
+    pub const fn plain() -> Self {
+        Self {
+            foreground: None,
+            background: None,
+            bold: None,
+            italic: None,
+            dimmed: None,
+        }
+    }
+
+    pub const fn on(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    pub const fn bold(mut self) -> Self {
+        self.bold = Some(true);
+        self
+    }
+
+    pub const fn italic(mut self) -> Self {
+        self.italic = Some(true);
+        self
+    }
+
+    pub const fn dimmed(mut self) -> Self {
+        self.dimmed = Some(true);
+        self
+    }
+
+    /// Undoes the dimming of the styles this one is overlaid on.
+    pub const fn undimmed(mut self) -> Self {
+        self.dimmed = Some(false);
+        self
+    }
+
+    /// Returns this style with the attributes set in `other` replacing its own.
+    pub fn overlay(self, other: Self) -> Self {
+        Self {
+            foreground: other.foreground.or(self.foreground),
+            background: other.background.or(self.background),
+            bold: other.bold.or(self.bold),
+            italic: other.italic.or(self.italic),
+            dimmed: other.dimmed.or(self.dimmed),
+        }
+    }
+
+    /// Returns this style with its colors replaced by the closest ones the terminal can display.
+    pub fn degraded(self, depth: ColorDepth) -> Self {
+        Self {
+            foreground: self.foreground.map(|color| palette::degrade(color, depth)),
+            background: self.background.map(|color| palette::degrade(color, depth)),
+            ..self
+        }
+    }
+
+    pub fn apply(&self, text: impl Into<ColoredString>) -> ColoredString {
+        let mut styled = text.into();
+        if let Some(foreground) = self.foreground {
+            styled = styled.color(foreground);
+        }
+        if let Some(background) = self.background {
+            styled = styled.on_color(background);
+        }
+        if self.bold == Some(true) {
+            styled = styled.bold();
+        }
+        if self.italic == Some(true) {
+            styled = styled.italic();
+        }
+        if self.dimmed == Some(true) {
+            styled = styled.dimmed();
+        }
+        styled
+    }
+}
+
+fn deserialize_color<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<Color>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|color| Color::from_str(&color).map_err(|()| D::Error::custom(format!("unknown color `{color}`"))))
+        .transpose()
+}
+
+fn deserialize_color_map<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<HashMap<String, Color>, D::Error> {
+    HashMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(key, color)| {
+            Color::from_str(&color)
+                .map(|color| (key, color))
+                .map_err(|()| D::Error::custom(format!("unknown color `{color}`")))
+        })
+        .collect()
+}
+
+/// The styles of a log level: the level's badge, and the messages logged with it.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LevelStyle {
+    pub badge: Style,
+    pub message: Style,
+}
+
+impl LevelStyle {
+    const fn new(foreground: Color, color: Color) -> Self {
+        Self {
+            badge: Style::fg(foreground).on(color).bold(),
+            message: Style::fg(color),
+        }
+    }
+
+    fn overlay(self, other: Self) -> Self {
+        Self {
+            badge: self.badge.overlay(other.badge),
+            message: self.message.overlay(other.message),
+        }
+    }
+
+    fn degraded(self, depth: ColorDepth) -> Self {
+        Self {
+            badge: self.badge.degraded(depth),
+            message: self.message.degraded(depth),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LevelStyles {
+    pub silent: LevelStyle,
+    pub verbose: LevelStyle,
+    pub debug: LevelStyle,
+    pub info: LevelStyle,
+    pub warning: LevelStyle,
+    pub error: LevelStyle,
+    pub fatal: LevelStyle,
+}
+
+impl LevelStyles {
+    const fn new(foreground: Color, colors: [Color; 7]) -> Self {
+        Self {
+            silent: LevelStyle::new(foreground, colors[0]),
+            verbose: LevelStyle::new(foreground, colors[1]),
+            debug: LevelStyle::new(foreground, colors[2]),
+            info: LevelStyle::new(foreground, colors[3]),
+            warning: LevelStyle::new(foreground, colors[4]),
+            error: LevelStyle::new(foreground, colors[5]),
+            fatal: LevelStyle::new(foreground, colors[6]),
+        }
+    }
+
+    fn overlay(self, other: Self) -> Self {
+        Self {
+            silent: self.silent.overlay(other.silent),
+            verbose: self.verbose.overlay(other.verbose),
+            debug: self.debug.overlay(other.debug),
+            info: self.info.overlay(other.info),
+            warning: self.warning.overlay(other.warning),
+            error: self.error.overlay(other.error),
+            fatal: self.fatal.overlay(other.fatal),
+        }
+    }
+
+    fn degraded(self, depth: ColorDepth) -> Self {
+        Self {
+            silent: self.silent.degraded(depth),
+            verbose: self.verbose.degraded(depth),
+            debug: self.debug.degraded(depth),
+            info: self.info.degraded(depth),
+            warning: self.warning.degraded(depth),
+            error: self.error.degraded(depth),
+            fatal: self.fatal.degraded(depth),
+        }
+    }
+}
+
+/// The styles of every element `LogcatFormatter` colors.
+///
+/// A theme deserialized from a file only contains the styles it sets, and is overlaid on a built-in theme.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub header: Style,
+    pub app_started: Style,
+    pub app_died: Style,
+    pub crash: Style,
+    pub suppressed: Style,
+    pub datetime: Style,
+    /// The style of times since the previous entry longer than `--slow-gap`, and of gap markers.
+    pub gap: Style,
+    pub pid: Style,
+    pub tid: Style,
+    pub tag: Style,
+    pub missing_tag: Style,
+    pub repetitions: Style,
+    pub field_key: Style,
+    pub field_value: Style,
+    /// The style of highlighted text; its background is used by highlight rules that don't specify a color.
+    pub highlight: Style,
+    /// Overlaid on the level's message style for stack trace frames.
+    pub stacktrace_entry: Style,
+    /// Overlaid on the level's message style for stack trace `Caused by:` and `Suppressed:` lines.
+    pub stacktrace_cause: Style,
+    /// Overlaid on the style of the line for the class of an exception.
+    pub exception: Style,
+    /// Overlaid on the style of stack trace frames for the parts of a frame.
+    pub frame_package: Style,
+    pub frame_class: Style,
+    pub frame_method: Style,
+    pub frame_location: Style,
+    /// Overlaid on the style of stack trace frames for the class and method of frames in `--my-code` packages.
+    pub my_code_frame: Style,
+    pub levels: LevelStyles,
+    /// Foreground colors of specific tags, taking precedence over the tag style and colors picked with `--color-by`.
+    #[serde(deserialize_with = "deserialize_color_map")]
+    pub tag_colors: HashMap<String, Color>,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            header: Style::fg(Color::BrightBlack),
+            app_started: Style::fg(Color::BrightGreen).bold(),
+            app_died: Style::fg(Color::BrightRed).bold(),
+            crash: Style::fg(Color::BrightRed).bold(),
+            suppressed: Style::fg(Color::BrightBlack).italic(),
+            datetime: Style::fg(Color::Magenta),
+            gap: Style::fg(Color::BrightYellow).bold(),
+            pid: Style::fg(Color::BrightBlack),
+            tid: Style::fg(Color::BrightBlack),
+            tag: Style::fg(Color::White),
+            missing_tag: Style::fg(Color::BrightBlack).italic(),
+            repetitions: Style::fg(Color::BrightBlack).italic(),
+            field_key: Style::fg(Color::BrightCyan),
+            field_value: Style::fg(Color::BrightWhite).bold(),
+            highlight: Style::fg(Color::Black).on(Color::Yellow).bold(),
+            stacktrace_entry: Style::plain().dimmed(),
+            stacktrace_cause: Style::plain(),
+            exception: Style::plain().bold(),
+            frame_package: Style::plain(),
+            frame_class: Style::plain().undimmed(),
+            frame_method: Style::plain().undimmed(),
+            frame_location: Style::fg(Color::Cyan),
+            my_code_frame: Style::fg(Color::BrightWhite).bold().undimmed(),
+            levels: LevelStyles::new(
+                Color::BrightWhite,
+                [
+                    Color::BrightBlack,
+                    Color::Cyan,
+                    Color::Blue,
+                    Color::Green,
+                    Color::Yellow,
+                    Color::Red,
+                    Color::BrightRed,
+                ],
+            ),
+            tag_colors: HashMap::new(),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            header: Style::fg(Color::Black).dimmed(),
+            app_started: Style::fg(Color::Green).bold(),
+            app_died: Style::fg(Color::Red).bold(),
+            crash: Style::fg(Color::Red).bold(),
+            suppressed: Style::fg(Color::Black).italic().dimmed(),
+            datetime: Style::fg(Color::Magenta),
+            gap: Style::fg(Color::Red).bold(),
+            pid: Style::fg(Color::Blue),
+            tid: Style::fg(Color::Blue),
+            tag: Style::fg(Color::Black).bold(),
+            missing_tag: Style::fg(Color::Black).italic().dimmed(),
+            repetitions: Style::fg(Color::Black).italic().dimmed(),
+            field_key: Style::fg(Color::Cyan),
+            field_value: Style::fg(Color::Black).bold(),
+            highlight: Style::fg(Color::Black).on(Color::BrightYellow).bold(),
+            stacktrace_entry: Style::plain().dimmed(),
+            stacktrace_cause: Style::plain(),
+            exception: Style::plain().bold(),
+            frame_package: Style::plain(),
+            frame_class: Style::plain().undimmed(),
+            frame_method: Style::plain().undimmed(),
+            frame_location: Style::fg(Color::Blue),
+            my_code_frame: Style::fg(Color::Magenta).bold().undimmed(),
+            levels: LevelStyles::new(
+                Color::White,
+                [
+                    Color::BrightBlack,
+                    Color::Cyan,
+                    Color::Blue,
+                    Color::Green,
+                    Color::TrueColor { r: 179, g: 107, b: 0 },
+                    Color::Red,
+                    Color::Magenta,
+                ],
+            ),
+            tag_colors: HashMap::new(),
+        }
+    }
+
+    pub fn solarized() -> Self {
+        const BASE01: Color = Color::TrueColor { r: 88, g: 110, b: 117 };
+        const BASE1: Color = Color::TrueColor { r: 147, g: 161, b: 161 };
+        const BASE3: Color = Color::TrueColor { r: 253, g: 246, b: 227 };
+        const YELLOW: Color = Color::TrueColor { r: 181, g: 137, b: 0 };
+        const ORANGE: Color = Color::TrueColor { r: 203, g: 75, b: 22 };
+        const RED: Color = Color::TrueColor { r: 220, g: 50, b: 47 };
+        const MAGENTA: Color = Color::TrueColor { r: 211, g: 54, b: 130 };
+        const VIOLET: Color = Color::TrueColor { r: 108, g: 113, b: 196 };
+        const BLUE: Color = Color::TrueColor { r: 38, g: 139, b: 210 };
+        const CYAN: Color = Color::TrueColor { r: 42, g: 161, b: 152 };
+        const GREEN: Color = Color::TrueColor { r: 133, g: 153, b: 0 };
+
+        Self {
+            header: Style::fg(BASE01),
+            app_started: Style::fg(GREEN).bold(),
+            app_died: Style::fg(RED).bold(),
+            crash: Style::fg(RED).bold(),
+            suppressed: Style::fg(BASE01).italic(),
+            datetime: Style::fg(VIOLET),
+            gap: Style::fg(ORANGE).bold(),
+            pid: Style::fg(BASE01),
+            tid: Style::fg(BASE01),
+            tag: Style::fg(BASE1),
+            missing_tag: Style::fg(BASE01).italic(),
+            repetitions: Style::fg(BASE01).italic(),
+            field_key: Style::fg(CYAN),
+            field_value: Style::fg(BASE1).bold(),
+            highlight: Style::fg(BASE3).on(YELLOW).bold(),
+            stacktrace_entry: Style::plain().dimmed(),
+            stacktrace_cause: Style::plain(),
+            exception: Style::plain().bold(),
+            frame_package: Style::plain(),
+            frame_class: Style::plain().undimmed(),
+            frame_method: Style::plain().undimmed(),
+            frame_location: Style::fg(CYAN),
+            my_code_frame: Style::fg(YELLOW).bold().undimmed(),
+            levels: LevelStyles::new(BASE3, [BASE01, CYAN, BLUE, GREEN, YELLOW, ORANGE, MAGENTA]),
+            tag_colors: HashMap::new(),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            header: Style::fg(Color::BrightWhite).bold(),
+            app_started: Style::fg(Color::Black).on(Color::BrightGreen).bold(),
+            app_died: Style::fg(Color::BrightWhite).on(Color::Red).bold(),
+            crash: Style::fg(Color::BrightWhite).on(Color::Red).bold(),
+            suppressed: Style::fg(Color::BrightWhite).italic(),
+            datetime: Style::fg(Color::BrightMagenta),
+            gap: Style::fg(Color::Black).on(Color::BrightYellow).bold(),
+            pid: Style::fg(Color::BrightWhite),
+            tid: Style::fg(Color::BrightWhite),
+            tag: Style::fg(Color::BrightWhite).bold(),
+            missing_tag: Style::fg(Color::BrightWhite).italic(),
+            repetitions: Style::fg(Color::BrightWhite).italic(),
+            field_key: Style::fg(Color::BrightCyan).bold(),
+            field_value: Style::fg(Color::BrightWhite).bold(),
+            highlight: Style::fg(Color::Black).on(Color::BrightYellow).bold(),
+            stacktrace_entry: Style::plain(),
+            stacktrace_cause: Style::plain().bold(),
+            exception: Style::plain().bold(),
+            frame_package: Style::fg(Color::White),
+            frame_class: Style::plain(),
+            frame_method: Style::plain().bold(),
+            frame_location: Style::fg(Color::BrightCyan),
+            my_code_frame: Style::fg(Color::Black).on(Color::BrightCyan).bold(),
+            levels: LevelStyles::new(
+                Color::Black,
+                [
+                    Color::White,
+                    Color::BrightCyan,
+                    Color::BrightBlue,
+                    Color::BrightGreen,
+                    Color::BrightYellow,
+                    Color::BrightRed,
+                    Color::BrightMagenta,
+                ],
+            ),
+            tag_colors: HashMap::new(),
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "solarized" => Some(Self::solarized()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// Returns where the user's theme is looked up when no theme is given: `$XDG_CONFIG_HOME/nyancat/theme.toml`,
+    /// falling back to `~/.config/nyancat/theme.toml`.
+    pub fn user_theme_path() -> Option<PathBuf> {
+        let config_dir = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("nyancat").join("theme.toml"))
+    }
+
+    /// Loads a theme from a TOML file, overlaying its styles on the built-in theme named by its `base` key.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).map_err(|e| anyhow!("failed to read theme file `{}`: {e}", path.display()))?;
+        Self::parse(&content).map_err(|e| anyhow!("invalid theme file `{}`: {e}", path.display()))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let mut table = toml::from_str::<toml::Table>(content)?;
+        let base = match table.remove("base") {
+            Some(toml::Value::String(base)) => base,
+            Some(base) => return Err(anyhow!("expected the base theme to be a string, found `{base}`")),
+            None => BUILTIN_THEMES[0].to_owned(),
+        };
+        let base = Self::builtin(&base).ok_or_else(|| {
+            anyhow!(
+                "unknown base theme `{base}`, expected one of: {}",
+                BUILTIN_THEMES.join(", ")
+            )
+        })?;
+
+        Ok(base.overlay(table.try_into()?))
+    }
+
+    fn overlay(self, other: Self) -> Self {
+        Self {
+            header: self.header.overlay(other.header),
+            app_started: self.app_started.overlay(other.app_started),
+            app_died: self.app_died.overlay(other.app_died),
+            crash: self.crash.overlay(other.crash),
+            suppressed: self.suppressed.overlay(other.suppressed),
+            datetime: self.datetime.overlay(other.datetime),
+            gap: self.gap.overlay(other.gap),
+            pid: self.pid.overlay(other.pid),
+            tid: self.tid.overlay(other.tid),
+            tag: self.tag.overlay(other.tag),
+            missing_tag: self.missing_tag.overlay(other.missing_tag),
+            repetitions: self.repetitions.overlay(other.repetitions),
+            field_key: self.field_key.overlay(other.field_key),
+            field_value: self.field_value.overlay(other.field_value),
+            highlight: self.highlight.overlay(other.highlight),
+            stacktrace_entry: self.stacktrace_entry.overlay(other.stacktrace_entry),
+            stacktrace_cause: self.stacktrace_cause.overlay(other.stacktrace_cause),
+            exception: self.exception.overlay(other.exception),
+            frame_package: self.frame_package.overlay(other.frame_package),
+            frame_class: self.frame_class.overlay(other.frame_class),
+            frame_method: self.frame_method.overlay(other.frame_method),
+            frame_location: self.frame_location.overlay(other.frame_location),
+            my_code_frame: self.my_code_frame.overlay(other.my_code_frame),
+            levels: self.levels.overlay(other.levels),
+            tag_colors: self
+                .tag_colors
+                .into_iter()
+                .chain(other.tag_colors)
+                .collect(),
+        }
+    }
+
+    /// Returns this theme with its colors replaced by the closest ones the terminal can display, e.g. so that the
+    /// truecolor solarized theme remains usable on a 16 color terminal.
+    pub fn degraded(self, depth: ColorDepth) -> Self {
+        Self {
+            header: self.header.degraded(depth),
+            app_started: self.app_started.degraded(depth),
+            app_died: self.app_died.degraded(depth),
+            crash: self.crash.degraded(depth),
+            suppressed: self.suppressed.degraded(depth),
+            datetime: self.datetime.degraded(depth),
+            gap: self.gap.degraded(depth),
+            pid: self.pid.degraded(depth),
+            tid: self.tid.degraded(depth),
+            tag: self.tag.degraded(depth),
+            missing_tag: self.missing_tag.degraded(depth),
+            repetitions: self.repetitions.degraded(depth),
+            field_key: self.field_key.degraded(depth),
+            field_value: self.field_value.degraded(depth),
+            highlight: self.highlight.degraded(depth),
+            stacktrace_entry: self.stacktrace_entry.degraded(depth),
+            stacktrace_cause: self.stacktrace_cause.degraded(depth),
+            exception: self.exception.degraded(depth),
+            frame_package: self.frame_package.degraded(depth),
+            frame_class: self.frame_class.degraded(depth),
+            frame_method: self.frame_method.degraded(depth),
+            frame_location: self.frame_location.degraded(depth),
+            my_code_frame: self.my_code_frame.degraded(depth),
+            levels: self.levels.degraded(depth),
+            tag_colors: self
+                .tag_colors
+                .into_iter()
+                .map(|(tag, color)| (tag, palette::degrade(color, depth)))
+                .collect(),
+        }
+    }
+
+    /// Returns the foreground and background colors of every style of the theme, and the colors of tags.
+    pub fn colors(&self) -> Vec<Color> {
+        let styles = [
+            self.header,
+            self.app_started,
+            self.app_died,
+            self.crash,
+            self.suppressed,
+            self.datetime,
+            self.gap,
+            self.pid,
+            self.tid,
+            self.tag,
+            self.missing_tag,
+            self.repetitions,
+            self.field_key,
+            self.field_value,
+            self.highlight,
+            self.stacktrace_entry,
+            self.stacktrace_cause,
+            self.exception,
+            self.frame_package,
+            self.frame_class,
+            self.frame_method,
+            self.frame_location,
+            self.my_code_frame,
+        ];
+        let level_styles = LogLevel::iter().flat_map(|level| {
+            let style = self.level(level);
+            [style.badge, style.message]
+        });
+
+        styles
+            .into_iter()
+            .chain(level_styles)
+            .flat_map(|style| [style.foreground, style.background])
+            .flatten()
+            .chain(self.tag_colors.values().copied())
+            .collect()
+    }
+
+    pub const fn level(&self, level: LogLevel) -> &LevelStyle {
+        match level {
+            LogLevel::Silent => &self.levels.silent,
+            LogLevel::Verbose => &self.levels.verbose,
+            LogLevel::Debug => &self.levels.debug,
+            LogLevel::Info => &self.levels.info,
+            LogLevel::Warning => &self.levels.warning,
+            LogLevel::Error => &self.levels.error,
+            LogLevel::Fatal => &self.levels.fatal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_theme_file() {
+        let theme = Theme::parse(
+            r##"
+            base = "light"
+            pid = { foreground = "bright blue", bold = true }
+            tag = { background = "#fdf6e3" }
+            tag_colors = { Finsky = "magenta" }
+
+            [levels.error]
+            badge = { foreground = "black" }
+            message = { italic = true }
+            "##,
+        )
+        .unwrap();
+        let light = Theme::light();
+
+        assert_eq!(theme.pid, Style::fg(Color::BrightBlue).bold());
+        assert_eq!(theme.tag, light.tag.on(Color::TrueColor { r: 253, g: 246, b: 227 }));
+        assert_eq!(theme.levels.error.badge, Style::fg(Color::Black).on(Color::Red).bold());
+        assert_eq!(theme.levels.error.message, Style::fg(Color::Red).italic());
+        assert_eq!(theme.levels.info, light.levels.info);
+        assert_eq!(theme.datetime, light.datetime);
+        assert_eq!(theme.tag_colors["Finsky"], Color::Magenta);
+    }
+
+    #[test]
+    fn test_parse_invalid_theme_files() {
+        let invalid_files = vec![
+            r#"base = "neon""#,                        // unknown base theme
+            r#"pid = { foreground = "bright pink" }"#, // unknown color
+            r#"pids = { foreground = "blue" }"#,       // unknown element
+            r"pid = { underline = true }",             // unknown attribute
+            r#"tag_colors = { Finsky = "pink" }"#,     // unknown tag color
+        ];
+
+        for file in invalid_files {
+            assert!(Theme::parse(file).is_err(), "Expected error for: {file}");
+        }
+    }
+}
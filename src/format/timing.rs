@@ -0,0 +1,167 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use strum::{Display, EnumString};
+use time::{Duration, UtcDateTime};
+
+use crate::log::LogEntry;
+
+/// What the `{time}` field of log entries shows.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum TimeMode {
+    /// The time the entry was logged at.
+    #[default]
+    Absolute,
+    /// The time since the first displayed entry.
+    Relative,
+    /// The time since the previous displayed entry.
+    Delta,
+    /// The time since the first displayed entry, followed by the time since the previous one.
+    Elapsed,
+}
+
+/// Which entries the time since the previous entry is measured between.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum DeltaBy {
+    Pid,
+    Tid,
+}
+
+/// The time between a displayed entry and the entries displayed before it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Intervals {
+    pub since_first: Duration,
+    /// The time since the previous entry, of the same process or thread with [`DeltaBy`].
+    pub since_previous: Duration,
+    /// The time since the previous entry of any process, which is zero for the first entry.
+    pub gap: Duration,
+}
+
+/// Keeps track of when the displayed entries were logged.
+pub struct Clock {
+    delta_by: Option<DeltaBy>,
+    first: Cell<Option<UtcDateTime>>,
+    previous: Cell<Option<UtcDateTime>>,
+    previous_by_id: RefCell<HashMap<u32, UtcDateTime>>,
+}
+
+impl Clock {
+    pub fn new(delta_by: Option<DeltaBy>) -> Self {
+        Self {
+            delta_by,
+            first: Cell::new(None),
+            previous: Cell::new(None),
+            previous_by_id: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Measures the intervals of `entry`, which is displayed after all entries observed so far.
+    pub fn observe(&self, entry: &LogEntry) -> Intervals {
+        let datetime = entry.datetime;
+        let first = self.first.get().unwrap_or(datetime);
+        self.first.set(Some(first));
+        let previous = self.previous.replace(Some(datetime)).unwrap_or(datetime);
+
+        let previous_of_id = self.delta_by.map_or(previous, |delta_by| {
+            let id = match delta_by {
+                DeltaBy::Pid => entry.pid,
+                DeltaBy::Tid => entry.tid,
+            };
+            self.previous_by_id
+                .borrow_mut()
+                .insert(id, datetime)
+                .unwrap_or(datetime)
+        });
+
+        Intervals {
+            since_first: datetime - first,
+            since_previous: datetime - previous_of_id,
+            gap: datetime - previous,
+        }
+    }
+}
+
+/// Formats an interval as seconds with millisecond precision, right-aligned so that the column keeps its width for
+/// intervals shorter than a day.
+pub fn format_interval(interval: Duration, signed: bool) -> String {
+    let seconds = interval.as_seconds_f64();
+    if signed {
+        format!("{seconds:>+10.3}")
+    } else {
+        format!("{seconds:>9.3}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_intervals() {
+        let clock = Clock::new(None);
+
+        assert_eq!(
+            clock.observe(&LogEntry::test("Finsky", "").with_pid(1)),
+            Intervals {
+                since_first: Duration::ZERO,
+                since_previous: Duration::ZERO,
+                gap: Duration::ZERO,
+            }
+        );
+        assert_eq!(
+            clock.observe(
+                &LogEntry::test("Finsky", "")
+                    .with_pid(2)
+                    .after(Duration::milliseconds(250))
+            ),
+            Intervals {
+                since_first: Duration::milliseconds(250),
+                since_previous: Duration::milliseconds(250),
+                gap: Duration::milliseconds(250),
+            }
+        );
+        assert_eq!(
+            clock.observe(
+                &LogEntry::test("Finsky", "")
+                    .with_pid(1)
+                    .after(Duration::milliseconds(2250))
+            ),
+            Intervals {
+                since_first: Duration::milliseconds(2250),
+                since_previous: Duration::seconds(2),
+                gap: Duration::seconds(2),
+            }
+        );
+    }
+
+    #[test]
+    fn test_measure_intervals_by_pid() {
+        let clock = Clock::new(Some(DeltaBy::Pid));
+
+        clock.observe(&LogEntry::test("Finsky", "").with_pid(1));
+        let intervals = clock.observe(
+            &LogEntry::test("Finsky", "")
+                .with_pid(2)
+                .after(Duration::milliseconds(250)),
+        );
+        assert_eq!(intervals.since_previous, Duration::ZERO);
+        let intervals = clock.observe(
+            &LogEntry::test("Finsky", "")
+                .with_pid(1)
+                .after(Duration::milliseconds(500)),
+        );
+        assert_eq!(intervals.since_previous, Duration::milliseconds(500));
+        assert_eq!(intervals.gap, Duration::milliseconds(250));
+    }
+
+    #[test]
+    fn test_format_interval() {
+        assert_eq!(format_interval(Duration::milliseconds(1250), false), "    1.250");
+        assert_eq!(format_interval(Duration::milliseconds(12), true), "    +0.012");
+        assert_eq!(format_interval(Duration::seconds(-3), true), "    -3.000");
+    }
+}
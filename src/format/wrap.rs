@@ -0,0 +1,317 @@
+use std::{
+    cell::Cell,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use strum::{Display, EnumString};
+use unicode_width::UnicodeWidthChar;
+
+const ESCAPE: char = '\x1b';
+const RESET: &str = "\x1b[0m";
+const ELLIPSIS: char = '…';
+
+/// The narrowest wrapped message; hanging indents that would leave less room than this are dropped.
+const MIN_WRAPPED_WIDTH: usize = 20;
+
+/// How lines longer than the terminal are printed.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum WrapMode {
+    /// Wrap at word boundaries, continuing under the message column.
+    #[default]
+    Indent,
+    /// Wrap at word boundaries, continuing at the start of the next line.
+    Terminal,
+    /// Leave long lines as they are.
+    None,
+    /// Cut long lines at the edge of the terminal.
+    Truncate,
+}
+
+/// The width of the terminal stdout is connected to, kept up to date when the terminal is resized.
+pub struct TerminalWidth {
+    width: Cell<Option<usize>>,
+    resized: Arc<AtomicBool>,
+}
+
+impl TerminalWidth {
+    pub fn new() -> Self {
+        let resized = Arc::new(AtomicBool::new(false));
+
+        #[cfg(unix)]
+        if let Err(e) = signal_hook::flag::register(signal_hook::consts::SIGWINCH, Arc::clone(&resized)) {
+            eprintln!("warning: failed to watch for terminal resizes: {e}");
+        }
+
+        Self {
+            width: Cell::new(Self::query()),
+            resized,
+        }
+    }
+
+    /// Returns the width of the terminal, or `None` if stdout isn't a terminal.
+    pub fn get(&self) -> Option<usize> {
+        if self.resized.swap(false, Ordering::Relaxed) {
+            self.width.set(Self::query());
+        }
+        self.width.get()
+    }
+
+    fn query() -> Option<usize> {
+        terminal_size::terminal_size().map(|(width, _)| usize::from(width.0))
+    }
+}
+
+/// How a formatted line is fitted into the terminal.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct LineWrap {
+    pub mode: WrapMode,
+    pub width: usize,
+    /// The column continuation lines start at in [`WrapMode::Indent`].
+    pub indent: usize,
+}
+
+impl LineWrap {
+    /// Fits `line`, which may contain ANSI escape sequences, into the terminal.
+    pub fn apply(&self, line: &str) -> String {
+        match self.mode {
+            WrapMode::None => line.to_owned(),
+            WrapMode::Indent if self.indent + MIN_WRAPPED_WIDTH <= self.width => wrap(line, self.width, self.indent),
+            WrapMode::Indent | WrapMode::Terminal => wrap(line, self.width, 0),
+            WrapMode::Truncate => truncate(line, self.width),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Token<'a> {
+    Escape(&'a str),
+    Char(char),
+}
+
+/// Splits `line` into ANSI escape sequences and visible characters.
+fn tokenize(line: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((start, char)) = chars.next() {
+        if char != ESCAPE {
+            tokens.push(Token::Char(char));
+            continue;
+        }
+
+        let mut end = start + char.len_utf8();
+        if let Some(&(_, '[')) = chars.peek() {
+            chars.next();
+            end += 1;
+            // Parameters and intermediate bytes are followed by a single final byte.
+            for (index, char) in chars.by_ref() {
+                end = index + char.len_utf8();
+                if ('\x40'..='\x7e').contains(&char) {
+                    break;
+                }
+            }
+        }
+        tokens.push(Token::Escape(&line[start..end]));
+    }
+
+    tokens
+}
+
+/// Returns the number of columns `char` takes in a terminal, 2 for wide characters and 0 for combining ones.
+pub fn char_width(char: char) -> usize {
+    char.width().unwrap_or(0)
+}
+
+/// Returns the number of columns `text` takes in a terminal, ignoring ANSI escape sequences.
+pub fn display_width(text: &str) -> usize {
+    tokenize(text)
+        .into_iter()
+        .map(|token| match token {
+            Token::Escape(_) => 0,
+            Token::Char(char) => char_width(char),
+        })
+        .sum()
+}
+
+/// Wraps `line` at word boundaries so that no line is wider than `width`, starting continuation lines at `indent`.
+///
+/// Styles are reset before each line break and restored after the indent, so indents are never colored.
+fn wrap(line: &str, width: usize, indent: usize) -> String {
+    let tokens = tokenize(line);
+    let mut wrapped = String::with_capacity(line.len());
+    let mut active_styles = Vec::new();
+    let mut column = 0;
+    let mut previous_char = None;
+
+    let break_line = |wrapped: &mut String, active_styles: &[&str], column: &mut usize| {
+        wrapped.truncate(wrapped.trim_end_matches(' ').len());
+        if !active_styles.is_empty() {
+            wrapped.push_str(RESET);
+        }
+        wrapped.push('\n');
+        wrapped.push_str(&" ".repeat(indent));
+        for style in active_styles {
+            wrapped.push_str(style);
+        }
+        *column = indent;
+    };
+
+    for (index, token) in tokens.iter().enumerate() {
+        let char = match *token {
+            Token::Escape(escape) => {
+                if escape == RESET {
+                    active_styles.clear();
+                } else {
+                    active_styles.push(escape);
+                }
+                wrapped.push_str(escape);
+                continue;
+            },
+            Token::Char(char) => char,
+        };
+
+        if char == ' ' {
+            // Spaces at the edge of the terminal are dropped rather than starting the continuation line.
+            if column < width {
+                wrapped.push(char);
+                column += 1;
+            }
+            previous_char = Some(char);
+            continue;
+        }
+
+        let starts_word = previous_char.is_none_or(|previous| previous == ' ');
+        if starts_word && column > indent {
+            let word_width = word_width(&tokens[index..]);
+            if column + word_width > width && word_width <= width - indent {
+                break_line(&mut wrapped, &active_styles, &mut column);
+            }
+        }
+
+        let char_width = char_width(char);
+        if column + char_width > width && column > indent {
+            break_line(&mut wrapped, &active_styles, &mut column);
+        }
+        wrapped.push(char);
+        column += char_width;
+        previous_char = Some(char);
+    }
+
+    wrapped
+}
+
+/// Returns the width of the word starting at the first token.
+fn word_width(tokens: &[Token]) -> usize {
+    tokens
+        .iter()
+        .filter_map(|token| match token {
+            Token::Escape(_) => None,
+            Token::Char(char) => Some(*char),
+        })
+        .take_while(|char| *char != ' ')
+        .map(char_width)
+        .sum()
+}
+
+/// Cuts `line` so that it isn't wider than `width`, marking the cut with an ellipsis.
+fn truncate(line: &str, width: usize) -> String {
+    if display_width(line) <= width {
+        return line.to_owned();
+    }
+
+    let mut truncated = String::with_capacity(line.len());
+    let mut column = 0;
+    // Whether a style is still applied where the line is cut, which must then be reset.
+    let mut is_styled = false;
+    for token in tokenize(line) {
+        match token {
+            Token::Escape(escape) => {
+                is_styled = escape != RESET;
+                truncated.push_str(escape);
+            },
+            Token::Char(char) => {
+                let char_width = char_width(char);
+                if column + char_width >= width {
+                    break;
+                }
+                truncated.push(char);
+                column += char_width;
+            },
+        }
+    }
+    truncated.push(ELLIPSIS);
+    if is_styled {
+        truncated.push_str(RESET);
+    }
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("Finsky"), 6);
+        assert_eq!(display_width("\x1b[1;42;97m I \x1b[0m"), 3);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("\x1b[38;2;255;136;0m→\x1b[0m"), 1);
+    }
+
+    #[test]
+    fn test_wrap_with_hanging_indent() {
+        let wrap = LineWrap {
+            mode: WrapMode::Indent,
+            width: 30,
+            indent: 8,
+        };
+
+        assert_eq!(
+            wrap.apply("Finsky  the quick brown fox jumps over the lazy dog"),
+            "Finsky  the quick brown fox\n        jumps over the lazy\n        dog"
+        );
+        assert_eq!(
+            wrap.apply("Finsky  \x1b[32mthe quick brown fox jumps\x1b[0m"),
+            "Finsky  \x1b[32mthe quick brown fox\x1b[0m\n        \x1b[32mjumps\x1b[0m"
+        );
+        assert_eq!(
+            wrap.apply("Finsky  0123456789012345678901234567890123456789"),
+            "Finsky  0123456789012345678901\n        234567890123456789"
+        );
+        assert_eq!(wrap.apply("Finsky  short"), "Finsky  short");
+    }
+
+    #[test]
+    fn test_wrap_without_room_for_indent() {
+        let wrap = LineWrap {
+            mode: WrapMode::Indent,
+            width: 24,
+            indent: 8,
+        };
+
+        assert_eq!(
+            wrap.apply("Finsky  the quick brown fox jumps"),
+            "Finsky  the quick brown\nfox jumps"
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        let wrap = LineWrap {
+            mode: WrapMode::Truncate,
+            width: 10,
+            indent: 0,
+        };
+
+        assert_eq!(wrap.apply("short"), "short");
+        assert_eq!(
+            wrap.apply("\x1b[32mthe quick brown fox\x1b[0m"),
+            "\x1b[32mthe quick…\x1b[0m"
+        );
+        assert_eq!(wrap.apply("日本語日本語"), "日本語日…");
+    }
+}
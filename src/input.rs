@@ -1,8 +1,11 @@
 use std::{
     fs::File,
-    io::{self, BufRead, BufReader, StdinLock},
+    io::{self, BufRead, BufReader, Stdin},
     process,
     process::{Command, Stdio},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
 };
 
 use anyhow::anyhow;
@@ -10,10 +13,18 @@ use bstr::{ByteSlice, io::BufReadExt};
 
 use crate::prelude::*;
 
+/// What's read from the input while waiting for its next line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputEvent {
+    Line(String),
+    /// No line arrived for a while.
+    Idle,
+}
+
 #[derive(Debug)]
 pub enum LogcatInput {
     File(BufReader<File>),
-    Stdin(StdinLock<'static>),
+    Stdin(Stdin),
     Process(BufReader<process::ChildStdout>),
 }
 
@@ -21,11 +32,35 @@ impl LogcatInput {
     pub fn lines(self) -> Box<dyn Iterator<Item = Result<String>>> {
         match self {
             Self::File(file) => Self::read_lines_from(file, "file"),
-            Self::Stdin(stdin) => Self::read_lines_from(stdin, "stdin"),
+            Self::Stdin(stdin) => Self::read_lines_from(stdin.lock(), "stdin"),
             Self::Process(process) => Self::read_lines_from(process, "logcat process stdout"),
         }
     }
 
+    /// Reads the lines on a thread of their own, yielding [`InputEvent::Idle`] whenever no line arrives within
+    /// `idle_timeout`, so that the lines can be waited for without blocking everything else.
+    pub fn lines_in_background(self, idle_timeout: Duration) -> impl Iterator<Item = Result<InputEvent>> {
+        // Bounded, so that lines aren't read much faster than they're printed.
+        let (sender, receiver) = mpsc::sync_channel(1024);
+        thread::spawn(move || {
+            for line in self.lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        std::iter::from_fn(move || match receiver.recv_timeout(idle_timeout) {
+            Ok(line) => Some(line.map(InputEvent::Line)),
+            Err(RecvTimeoutError::Timeout) => Some(Ok(InputEvent::Idle)),
+            Err(RecvTimeoutError::Disconnected) => None,
+        })
+    }
+
+    pub const fn is_process(&self) -> bool {
+        matches!(self, Self::Process(_))
+    }
+
     pub fn from_file(path: &str) -> Result<Self> {
         let file = File::open(path)?;
         let reader = BufReader::new(file);
@@ -33,7 +68,7 @@ impl LogcatInput {
     }
 
     pub fn from_stdin() -> Self {
-        Self::Stdin(io::stdin().lock())
+        Self::Stdin(io::stdin())
     }
 
     pub fn from_process() -> Result<Self> {
@@ -1,15 +1,26 @@
+use serde::{Deserialize, Serialize};
 use strum::Display;
 use time::UtcDateTime;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// A line of logcat output.
+///
+/// Lines are serialized as JSON objects whose `type` is `header` or `entry`, with the fields of the header or entry.
+/// This schema is stable: fields may be added, but are never renamed or removed.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
 pub enum LogLine {
-    Header(String),
+    /// A `--------- beginning of main` line, serialized as `{"type": "header", "buffer": "main"}`.
+    Header {
+        buffer: String,
+    },
     Entry(LogEntry),
 }
 
 impl LogLine {
-    pub fn header(header: &str) -> Self {
-        Self::Header(header.to_owned())
+    pub fn header(buffer: &str) -> Self {
+        Self::Header {
+            buffer: buffer.to_owned(),
+        }
     }
 
     pub const fn entry(
@@ -24,8 +35,12 @@ impl LogLine {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// An entry of logcat output, serialized as e.g.
+/// `{"type": "entry", "datetime": "2025-10-01T12:10:36.746Z", "level": "info", "pid": 14344, "tid": 14344,
+/// "tag": "Finsky", "message": "..."}`, with the datetime in RFC 3339 and the level in lowercase.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct LogEntry {
+    #[serde(with = "rfc3339")]
     pub datetime: UtcDateTime,
     pub pid: u32,
     pub tid: u32,
@@ -47,20 +62,107 @@ impl LogEntry {
     }
 }
 
-#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Display, strum::EnumString, strum::EnumIter)]
+/// The time entries built for tests are logged at.
+#[cfg(test)]
+const TEST_DATETIME: UtcDateTime = time_macros::utc_datetime!(2025-10-01 12:10:00);
+
+/// Builds entries for tests, e.g. `LogEntry::test("Finsky", "...").with_level(LogLevel::Error)`.
+#[cfg(test)]
+impl LogEntry {
+    /// Returns an entry logged by the main thread of pid 1000 at 12:10:00 on 2025-10-01, at info level.
+    pub fn test(tag: &str, message: &str) -> Self {
+        Self::new(
+            TEST_DATETIME,
+            1000,
+            1000,
+            LogLevel::Info,
+            tag.to_owned(),
+            message.to_owned(),
+        )
+    }
+
+    pub const fn with_datetime(mut self, datetime: UtcDateTime) -> Self {
+        self.datetime = datetime;
+        self
+    }
+
+    /// Logs the entry `duration` after its current time.
+    pub fn after(mut self, duration: time::Duration) -> Self {
+        self.datetime += duration;
+        self
+    }
+
+    /// Logs the entry from the main thread of `pid`, whose tid is the same.
+    pub const fn with_pid(mut self, pid: u32) -> Self {
+        self.pid = pid;
+        self.tid = pid;
+        self
+    }
+
+    pub const fn with_tid(mut self, tid: u32) -> Self {
+        self.tid = tid;
+        self
+    }
+
+    pub const fn with_level(mut self, level: LogLevel) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Ord,
+    PartialOrd,
+    Eq,
+    PartialEq,
+    Display,
+    strum::EnumString,
+    strum::EnumIter,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
 pub enum LogLevel {
     #[strum(serialize = "S")]
+    #[serde(alias = "S")]
     Silent,
     #[strum(serialize = "V")]
+    #[serde(alias = "V")]
     Verbose,
     #[strum(serialize = "D")]
+    #[serde(alias = "D")]
     Debug,
     #[strum(serialize = "I")]
+    #[serde(alias = "I")]
     Info,
     #[strum(serialize = "W")]
+    #[serde(alias = "W")]
     Warning,
     #[strum(serialize = "E")]
+    #[serde(alias = "E")]
     Error,
     #[strum(serialize = "F")]
+    #[serde(alias = "F")]
     Fatal,
 }
+
+/// Serializes datetimes in RFC 3339, e.g. `2025-10-01T12:10:36.746Z`.
+mod rfc3339 {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error as _, ser::Error as _};
+    use time::{OffsetDateTime, UtcDateTime, format_description::well_known::Rfc3339};
+
+    pub fn serialize<S: Serializer>(datetime: &UtcDateTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let datetime = datetime.format(&Rfc3339).map_err(S::Error::custom)?;
+        serializer.serialize_str(&datetime)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<UtcDateTime, D::Error> {
+        let datetime = String::deserialize(deserializer)?;
+        OffsetDateTime::parse(&datetime, &Rfc3339)
+            .map(UtcDateTime::from)
+            .map_err(D::Error::custom)
+    }
+}
@@ -2,13 +2,26 @@
 
 use crate::prelude::*;
 
+mod app;
 mod cli;
+mod crash;
+mod dedupe;
+mod fields;
 mod filter;
 mod format;
 mod input;
 mod log;
+mod output;
 mod parse;
 mod prelude;
+mod rate_limit;
+mod retrace;
+mod stacktrace;
+mod stats;
+mod symbolize;
+mod timeline;
+mod tombstone;
+mod zone;
 
 fn main() -> Result<()> {
     cli::run()
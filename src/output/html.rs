@@ -0,0 +1,356 @@
+use std::collections::HashSet;
+
+use colored::{Color, ColoredString, Styles};
+use strum::IntoEnumIterator;
+
+use crate::{
+    format::{FormattedLogLine, Theme, rgb},
+    log::{LogEntry, LogLevel},
+    stacktrace::{Relation, StacktraceLine},
+};
+
+const FONT_FAMILY: &str = "ui-monospace, SFMono-Regular, Menlo, Consolas, monospace";
+
+/// The attributes of spans that are styled by a CSS class of the same name.
+const STYLE_CLASSES: [(Styles, &str); 4] = [
+    (Styles::Bold, "bold"),
+    (Styles::Dimmed, "dimmed"),
+    (Styles::Italic, "italic"),
+    (Styles::Underline, "underline"),
+];
+
+/// Hides the entries whose level is unchecked, and those that don't contain the text of the filter.
+const SCRIPT: &str = r"const log = document.getElementById('log');
+for (const toggle of document.querySelectorAll('#levels input')) {
+  toggle.addEventListener('change', () => log.classList.toggle('hide-' + toggle.value, !toggle.checked));
+}
+document.getElementById('filter').addEventListener('input', (event) => {
+  const text = event.target.value.toLowerCase();
+  for (const entry of log.children) {
+    entry.hidden = text !== '' && !entry.textContent.toLowerCase().includes(text);
+  }
+});";
+
+/// Writes formatted log lines as a standalone HTML page, with the colors of the theme as CSS classes.
+///
+/// Each line is an element of its own, with the class of its level so that levels can be hidden, and a Java stack
+/// trace is wrapped in a `<details>` element, whose summary is the line of the thrown exception, so that it can be
+/// collapsed. The text filter matches the whole stack trace.
+pub struct HtmlWriter {
+    /// The names of the colors with a CSS class, see [`color_name`]. Other colors, e.g. those picked with
+    /// `--color-by`, are set inline.
+    classes: HashSet<String>,
+    header: String,
+    /// The line of a thrown exception, held back until the next line tells whether a stack trace follows it.
+    exception: Option<HeldLine>,
+    /// The pid and tid of the stack trace being written.
+    stacktrace: Option<(u32, u32)>,
+}
+
+struct HeldLine {
+    html: String,
+    level: LogLevel,
+    thread: (u32, u32),
+}
+
+impl HtmlWriter {
+    pub fn new(theme: &Theme, source: &str) -> Self {
+        let mut writer = Self {
+            classes: HashSet::new(),
+            header: String::new(),
+            exception: None,
+            stacktrace: None,
+        };
+
+        let color_classes = theme
+            .colors()
+            .into_iter()
+            .filter_map(|color| {
+                let name = color_name(color);
+                writer.classes.insert(name.clone()).then(|| {
+                    let hex = hex(color);
+                    format!(".fg-{name} {{ color: {hex}; }}\n.bg-{name} {{ background-color: {hex}; }}\n")
+                })
+            })
+            .collect::<String>();
+
+        let levels = LogLevel::iter()
+            .filter(|&level| level != LogLevel::Silent)
+            .collect::<Vec<_>>();
+        let level_toggles = levels
+            .iter()
+            .map(|&level| {
+                let badge = writer.render_span(&theme.level(level).badge.apply(format!(" {level} ")));
+                format!("<label><input type=\"checkbox\" value=\"{level}\" checked>{badge}</label>")
+            })
+            .collect::<Vec<_>>()
+            .concat();
+        let hidden_levels = levels
+            .iter()
+            .map(|level| format!("#log.hide-{level} .level-{level} {{ display: none; }}\n"))
+            .collect::<Vec<_>>()
+            .concat();
+
+        // The background isn't part of themes, which are made for the background of the terminal. Themes with light
+        // tags, like the default one, are meant for a dark background.
+        let is_dark = theme
+            .tag
+            .foreground
+            .is_none_or(|color| luminance(color) > 128);
+        let (background, foreground, border) = if is_dark {
+            ("#1e1e1e", "#e5e5e5", "#3c3c3c")
+        } else {
+            ("#ffffff", "#1e1e1e", "#d0d0d0")
+        };
+
+        writer.header = format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ margin: 0; background: {background}; color: {foreground}; font: 13px/1.4 {FONT_FAMILY}; }}
+header {{ position: sticky; top: 0; display: flex; gap: 1em; align-items: center; padding: 0.5em 1em; background: {background}; border-bottom: 1px solid {border}; }}
+header label {{ cursor: pointer; }}
+#filter {{ flex: 1; font: inherit; }}
+#log {{ padding: 0.5em 1em; }}
+.line {{ display: block; white-space: pre; }}
+summary {{ display: block; cursor: pointer; }}
+details:not([open]) > summary > .line::after {{ content: " [+]"; opacity: 0.6; }}
+.bold {{ font-weight: bold; }}
+.dimmed {{ opacity: 0.6; }}
+.italic {{ font-style: italic; }}
+.underline {{ text-decoration: underline; }}
+{color_classes}{hidden_levels}</style>
+</head>
+<body>
+<header>
+<span id="levels">{level_toggles}</span>
+<input type="search" id="filter" placeholder="Filter" autofocus>
+</header>
+<main id="log">
+"#,
+            title = escape(source),
+        );
+        writer
+    }
+
+    /// Returns the start of the page, up to the first line.
+    pub fn header(&self) -> &str {
+        &self.header
+    }
+
+    /// Returns the HTML of `line`, along with that of the lines held back before it. `entry` is the entry the line
+    /// was formatted from, if any.
+    pub fn write(&mut self, line: &FormattedLogLine, entry: Option<&LogEntry>) -> String {
+        let html = self.render_line(line, entry.map(|entry| entry.level));
+        let thread = entry.map(|entry| (entry.pid, entry.tid));
+        let continues_stacktrace = entry.is_some_and(|entry| match StacktraceLine::parse(&entry.message) {
+            Some(StacktraceLine::Exception(exception)) => exception.relation != Relation::Thrown,
+            line => line.is_some(),
+        });
+
+        let mut output = String::new();
+        if let Some(stacktrace) = self.stacktrace {
+            if continues_stacktrace && thread == Some(stacktrace) {
+                return html;
+            }
+            output.push_str("</details>\n");
+            self.stacktrace = None;
+        }
+
+        if let Some(exception) = self.exception.take() {
+            if continues_stacktrace && thread == Some(exception.thread) {
+                self.stacktrace = Some(exception.thread);
+                return format!(
+                    "{output}<details open class=\"level-{}\"><summary>{}</summary>\n{html}",
+                    exception.level,
+                    exception.html.trim_end()
+                );
+            }
+            output.push_str(&exception.html);
+        }
+
+        if let Some(entry) = entry
+            && let Some(StacktraceLine::Exception(exception)) = StacktraceLine::parse(&entry.message)
+            && exception.relation == Relation::Thrown
+        {
+            self.exception = Some(HeldLine {
+                html,
+                level: entry.level,
+                thread: (entry.pid, entry.tid),
+            });
+            return output;
+        }
+
+        output.push_str(&html);
+        output
+    }
+
+    /// Returns the lines held back and the end of the page.
+    pub fn finish(&mut self) -> String {
+        let mut output = self
+            .exception
+            .take()
+            .map(|exception| exception.html)
+            .unwrap_or_default();
+        if self.stacktrace.take().is_some() {
+            output.push_str("</details>\n");
+        }
+        format!("{output}</main>\n<script>\n{SCRIPT}\n</script>\n</body>\n</html>\n")
+    }
+
+    fn render_line(&self, line: &FormattedLogLine, level: Option<LogLevel>) -> String {
+        let marker = line
+            .marker()
+            .map(|marker| format!("<span class=\"line\">{}</span>\n", self.render_span(marker)))
+            .unwrap_or_default();
+        let class = level.map_or_else(|| "line".to_owned(), |level| format!("line level-{level}"));
+        let mut html = format!("{marker}<span class=\"{class}\">");
+        for (i, part) in line.parts().enumerate() {
+            if i > 0 {
+                html.push(' ');
+            }
+            for span in part.spans() {
+                html.push_str(&self.render_span(span));
+            }
+        }
+        html.push_str("</span>\n");
+        html
+    }
+
+    fn render_span(&self, span: &ColoredString) -> String {
+        let mut classes = Vec::new();
+        let mut styles = Vec::new();
+        for (color, prefix, property) in [(span.fgcolor, "fg", "color"), (span.bgcolor, "bg", "background-color")] {
+            let Some(color) = color else {
+                continue;
+            };
+            let name = color_name(color);
+            if self.classes.contains(&name) {
+                classes.push(format!("{prefix}-{name}"));
+            } else {
+                styles.push(format!("{property}: {}", hex(color)));
+            }
+        }
+        for (style, class) in STYLE_CLASSES {
+            if span.style.contains(style) {
+                classes.push(class.to_owned());
+            }
+        }
+
+        let text = escape(&span.input);
+        if classes.is_empty() && styles.is_empty() {
+            return text;
+        }
+
+        let class = if classes.is_empty() {
+            String::new()
+        } else {
+            format!(" class=\"{}\"", classes.join(" "))
+        };
+        let style = if styles.is_empty() {
+            String::new()
+        } else {
+            format!(" style=\"{}\"", styles.join("; "))
+        };
+        format!("<span{class}{style}>{text}</span>")
+    }
+}
+
+/// Returns the name of `color` in CSS classes, e.g. `bright-red`, `ansi-208` or `rgb-b36b00`.
+fn color_name(color: Color) -> String {
+    match color {
+        Color::TrueColor { r, g, b } => format!("rgb-{r:02x}{g:02x}{b:02x}"),
+        Color::AnsiColor(code) => format!("ansi-{code}"),
+        named => {
+            let mut name = String::new();
+            for c in format!("{named:?}").chars() {
+                if c.is_ascii_uppercase() && !name.is_empty() {
+                    name.push('-');
+                }
+                name.push(c.to_ascii_lowercase());
+            }
+            name
+        },
+    }
+}
+
+fn hex(color: Color) -> String {
+    let (r, g, b) = rgb(color);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn luminance(color: Color) -> u32 {
+    let (r, g, b) = rgb(color);
+    (299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b)) / 1000
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::LogcatFormatter;
+
+    #[test]
+    fn test_write_html() {
+        let formatter = LogcatFormatter::new();
+        let mut writer = HtmlWriter::new(formatter.theme(), "bugreport <1>.txt");
+        assert!(
+            writer
+                .header()
+                .contains("<title>bugreport &lt;1&gt;.txt</title>")
+        );
+        assert!(
+            writer
+                .header()
+                .contains(".fg-bright-red { color: #ff0000; }")
+        );
+
+        let entry = |tid: u32, message: &str| {
+            LogEntry::test("AndroidRuntime", message)
+                .with_level(LogLevel::Error)
+                .with_tid(tid)
+        };
+        let mut html = String::new();
+        for entry in [
+            entry(8123, "java.lang.IllegalStateException: <boom>"),
+            entry(8123, "\tat com.example.Foo.bar(Foo.java:42)"),
+            entry(8123, "Caused by: java.io.IOException"),
+            entry(8140, "\tat com.example.Other.run(Other.java:7)"),
+            entry(8123, "java.lang.IllegalStateException: not a stack trace"),
+        ] {
+            html.push_str(&writer.write(&formatter.format_log_entry(&entry), Some(&entry)));
+        }
+        html.push_str(&writer.finish());
+
+        assert_eq!(
+            html.matches("<details open class=\"level-E\"><summary>")
+                .count(),
+            1
+        );
+        assert_eq!(html.matches("</details>").count(), 1);
+        let stacktrace = &html[html.find("<details").unwrap()..html.find("</details>").unwrap()];
+        assert!(stacktrace.contains("&lt;boom&gt;</span></span></summary>"));
+        assert!(stacktrace.contains("Foo.java:42"));
+        assert!(stacktrace.contains("java.io.IOException"));
+        assert!(!stacktrace.contains("Other.java"));
+        assert!(html.contains("not a stack trace"));
+        assert!(html.contains("<span class=\"line level-E\">"));
+        assert!(html.ends_with("</html>\n"));
+    }
+}
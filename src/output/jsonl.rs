@@ -0,0 +1,84 @@
+use std::rc::Rc;
+
+use serde::Serialize;
+
+use crate::{app::ProcessNames, log::LogLine};
+
+/// Serializes log lines as JSON Lines, with the schema of [`LogLine`] extended by fields naming where entries come
+/// from:
+/// - `process`: the name of the process that logged an entry, if known.
+/// - `device`: the serial of the device, if known.
+/// - `source`: the input file, `adb logcat` or `stdin`.
+pub struct JsonlWriter {
+    process_names: Option<Rc<ProcessNames>>,
+    device: Option<String>,
+    source: String,
+}
+
+#[derive(Serialize)]
+struct Record<'a> {
+    #[serde(flatten)]
+    line: &'a LogLine,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    process: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device: Option<&'a str>,
+    source: &'a str,
+}
+
+impl JsonlWriter {
+    pub const fn new(process_names: Option<Rc<ProcessNames>>, device: Option<String>, source: String) -> Self {
+        Self {
+            process_names,
+            device,
+            source,
+        }
+    }
+
+    /// Serializes `line` as a single line of JSON.
+    pub fn to_json(&self, line: &LogLine) -> String {
+        let process = match line {
+            LogLine::Entry(entry) => self
+                .process_names
+                .as_ref()
+                .and_then(|process_names| process_names.get(entry.pid)),
+            LogLine::Header { .. } => None,
+        };
+        let record = Record {
+            line,
+            process,
+            device: self.device.as_deref(),
+            source: &self.source,
+        };
+        serde_json::to_string(&record).expect("log lines are always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time_macros::utc_datetime;
+
+    use super::*;
+    use crate::log::LogLevel;
+
+    #[test]
+    fn test_serialize_log_lines() {
+        let writer = JsonlWriter::new(None, Some("emulator-5554".to_owned()), "logcat.txt".to_owned());
+
+        assert_eq!(
+            writer.to_json(&LogLine::header("main")),
+            r#"{"type":"header","buffer":"main","device":"emulator-5554","source":"logcat.txt"}"#
+        );
+        assert_eq!(
+            writer.to_json(&LogLine::entry(
+                utc_datetime!(2025-10-01 12:10:36.746),
+                14344,
+                14372,
+                LogLevel::Info,
+                "Finsky".to_owned(),
+                "[2] SCH: \"jobFinished\"".to_owned(),
+            )),
+            r#"{"type":"entry","datetime":"2025-10-01T12:10:36.746Z","pid":14344,"tid":14372,"level":"info","tag":"Finsky","message":"[2] SCH: \"jobFinished\"","device":"emulator-5554","source":"logcat.txt"}"#
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use time::format_description::BorrowedFormatItem;
+use time_macros::format_description;
+
+use crate::{
+    log::{LogEntry, LogLine},
+    parse::MISSING_TAG,
+    zone::Zone,
+};
+
+const DATETIME_FORMAT: &[BorrowedFormatItem] =
+    format_description!("[month]-[day] [hour]:[minute]:[second].[subsecond digits:3]");
+
+/// Serializes log lines in the `threadtime` format of `adb logcat`, e.g.
+/// `10-01 12:10:36.746 14344 14372 I Finsky  : message`, so that they can be read by other logcat tools.
+pub struct LogcatWriter {
+    /// The time zone times are written in, as logcat writes them without an offset.
+    zone: Zone,
+}
+
+impl LogcatWriter {
+    pub const fn new(zone: Zone) -> Self {
+        Self { zone }
+    }
+
+    /// Serializes `line`, writing each line of the message of an entry with the prefix of the entry, as logcat does.
+    pub fn to_logcat(&self, line: &LogLine) -> String {
+        match line {
+            LogLine::Header { buffer } => format!("--------- beginning of {buffer}"),
+            LogLine::Entry(entry) => {
+                let prefix = self.prefix(entry);
+                entry
+                    .message
+                    .split('\n')
+                    .map(|message| format!("{prefix}{message}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            },
+        }
+    }
+
+    fn prefix(&self, entry: &LogEntry) -> String {
+        let datetime = self
+            .zone
+            .localize(entry.datetime)
+            .format(DATETIME_FORMAT)
+            .unwrap_or_else(|e| panic!("failed to format datetime: {e}"));
+        // Entries without a tag are written by logcat with an empty one.
+        let tag = if entry.tag == MISSING_TAG { "" } else { &entry.tag };
+        format!("{datetime} {:5} {:5} {} {tag:<8}: ", entry.pid, entry.tid, entry.level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time_macros::utc_datetime;
+
+    use super::*;
+    use crate::{log::LogLevel, parse::LogcatParser};
+
+    #[test]
+    fn test_serialize_threadtime() {
+        let writer = LogcatWriter::new(Zone::Utc);
+        let lines = [
+            "--------- beginning of main",
+            "10-01 12:10:36.746 14344 14372 I Finsky  : [2] SCH: jobFinished: 10-71.",
+            "10-01 12:10:37.001   812   812 W ActivityManager: Slow operation",
+        ];
+
+        let parser = LogcatParser::new();
+        for line in lines {
+            assert_eq!(writer.to_logcat(&parser.parse_log_line(line).unwrap()), line);
+        }
+
+        let entry = LogLine::entry(
+            utc_datetime!(2025-10-01 12:10:37.5),
+            1,
+            2,
+            LogLevel::Error,
+            MISSING_TAG.to_owned(),
+            "first\nsecond".to_owned(),
+        );
+        assert_eq!(
+            writer.to_logcat(&entry),
+            "10-01 12:10:37.500     1     2 E         : first\n10-01 12:10:37.500     1     2 E         : second"
+        );
+    }
+}
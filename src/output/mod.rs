@@ -0,0 +1,54 @@
+mod html;
+mod jsonl;
+mod logcat;
+mod rotate;
+mod split;
+
+use strum::{Display, EnumString};
+
+use crate::log::LogLine;
+
+pub use html::HtmlWriter;
+pub use jsonl::JsonlWriter;
+pub use logcat::LogcatWriter;
+pub use rotate::{RotatingFile, Rotation};
+pub use split::{SplitBy, SplitFormat, Splitter};
+
+/// How log lines are written to stdout.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum OutputFormat {
+    /// Formatted by `LogcatFormatter`, colored according to `--color`.
+    #[default]
+    Text,
+    /// Formatted like [`Self::Text`], without colors.
+    Plain,
+    /// In the `threadtime` format of logcat, see [`LogcatWriter`].
+    Logcat,
+    /// One JSON object per line, see [`JsonlWriter`].
+    Jsonl,
+    /// A standalone HTML page, see [`HtmlWriter`].
+    Html,
+}
+
+impl OutputFormat {
+    /// Returns whether lines are laid out by `LogcatFormatter`, rather than serialized as they are.
+    pub const fn is_formatted(self) -> bool {
+        !matches!(self, Self::Logcat | Self::Jsonl)
+    }
+}
+
+/// Serializes log lines in a format other tools can read.
+pub enum Serializer {
+    Logcat(LogcatWriter),
+    Jsonl(JsonlWriter),
+}
+
+impl Serializer {
+    pub fn serialize(&self, line: &LogLine) -> String {
+        match self {
+            Self::Logcat(writer) => writer.to_logcat(line),
+            Self::Jsonl(writer) => writer.to_json(line),
+        }
+    }
+}
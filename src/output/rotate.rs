@@ -0,0 +1,279 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use flate2::{Compression, write::GzEncoder};
+use lazy_regex::regex_captures;
+
+use crate::prelude::*;
+
+/// When a saved file is rotated.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Rotation {
+    /// Before the file would grow beyond the size, in bytes.
+    Size(u64),
+    /// Once the file has been written to for the duration.
+    Age(Duration),
+}
+
+impl FromStr for Rotation {
+    type Err = anyhow::Error;
+
+    /// Parses a size such as `100MB`, in `B`, `KB`, `MB` or `GB` (powers of 1024), or an age such as `1h`, in `s`,
+    /// `m`, `h` or `d`.
+    fn from_str(rotation: &str) -> Result<Self> {
+        let invalid =
+            || anyhow!("invalid rotation `{rotation}`, expected a size such as `100MB` or an age such as `1h`");
+        let (_, amount, unit) = regex_captures!(r"^(\d+)\s*([A-Za-z]+)$", rotation.trim()).ok_or_else(invalid)?;
+        let amount = amount.parse::<u64>().map_err(|_| invalid())?;
+        if amount == 0 {
+            return Err(anyhow!("invalid rotation `{rotation}`, it must be greater than 0"));
+        }
+
+        let seconds = |unit: u64| Ok(Self::Age(Duration::from_secs(amount.saturating_mul(unit))));
+        let bytes = |unit: u64| Ok(Self::Size(amount.saturating_mul(unit)));
+        // Ages are lowercase so that `m` is a minute, while sizes are case insensitive.
+        match unit {
+            "s" => seconds(1),
+            "m" => seconds(60),
+            "h" => seconds(60 * 60),
+            "d" => seconds(24 * 60 * 60),
+            _ => match unit.to_ascii_uppercase().as_str() {
+                "B" => bytes(1),
+                "KB" => bytes(1 << 10),
+                "MB" => bytes(1 << 20),
+                "GB" => bytes(1 << 30),
+                _ => Err(invalid()),
+            },
+        }
+    }
+}
+
+/// A file that lines are saved to, rotated like logrotate does: `FILE` is renamed to `FILE.1`, `FILE.1` to `FILE.2`
+/// and so on, and the oldest file is removed once there are more rotated files than are kept.
+pub struct RotatingFile {
+    path: PathBuf,
+    rotation: Option<Rotation>,
+    /// How many rotated files are kept.
+    keep: usize,
+    /// Whether rotated files are compressed with gzip, to `FILE.1.gz` and so on.
+    compress: bool,
+    /// Whether lines are flushed as they're written, so that lines of live input are saved even if nyancat is
+    /// interrupted.
+    flush_lines: bool,
+    writer: BufWriter<File>,
+    size: u64,
+    opened: Instant,
+    /// The compression of the last rotated file, which is done in the background so that it doesn't hold up the
+    /// display.
+    compression: Option<JoinHandle<Result<()>>>,
+}
+
+impl RotatingFile {
+    /// Creates the file, truncating it if it exists.
+    pub fn create(path: &Path, flush_lines: bool) -> Result<Self> {
+        Ok(Self {
+            path: path.to_owned(),
+            rotation: None,
+            keep: 0,
+            compress: false,
+            flush_lines,
+            writer: create_file(path)?,
+            size: 0,
+            opened: Instant::now(),
+            compression: None,
+        })
+    }
+
+    /// Rotates the file when `rotation` says, keeping `keep` rotated files.
+    pub const fn with_rotation(mut self, rotation: Option<Rotation>, keep: usize, compress: bool) -> Self {
+        self.rotation = rotation;
+        self.keep = keep;
+        self.compress = compress;
+        self
+    }
+
+    pub fn write_line(&mut self, line: &str) -> Result<()> {
+        let length = line.len() as u64 + 1;
+        let is_due = match self.rotation {
+            Some(Rotation::Size(size)) => self.size + length > size,
+            Some(Rotation::Age(age)) => self.opened.elapsed() >= age,
+            None => false,
+        };
+        // Empty files are never rotated, so lines longer than the size of files are still saved.
+        if is_due && self.size > 0 {
+            self.rotate()?;
+        }
+
+        writeln!(self.writer, "{line}").map_err(|e| anyhow!("failed to write to `{}`: {e}", self.path.display()))?;
+        self.size += length;
+        if self.flush_lines {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the lines written so far, and waits for the compression of the last rotated file.
+    pub fn finish(&mut self) -> Result<()> {
+        self.flush()?;
+        self.wait_for_compression();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer
+            .flush()
+            .map_err(|e| anyhow!("failed to write to `{}`: {e}", self.path.display()))
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        self.finish()?;
+
+        if self.keep > 0 {
+            let oldest = self.rotated_path(self.keep);
+            for path in [with_suffix(&oldest, ".gz"), oldest] {
+                remove_if_exists(&path)?;
+            }
+            for index in (1..self.keep).rev() {
+                let (from, to) = (self.rotated_path(index), self.rotated_path(index + 1));
+                rename_if_exists(&with_suffix(&from, ".gz"), &with_suffix(&to, ".gz"))?;
+                rename_if_exists(&from, &to)?;
+            }
+
+            let rotated = self.rotated_path(1);
+            rename_if_exists(&self.path, &rotated)?;
+            if self.compress {
+                self.compression = Some(thread::spawn(move || compress(&rotated)));
+            }
+        }
+
+        self.writer = create_file(&self.path)?;
+        self.size = 0;
+        self.opened = Instant::now();
+        Ok(())
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        with_suffix(&self.path, &format!(".{index}"))
+    }
+
+    fn wait_for_compression(&mut self) {
+        let Some(compression) = self.compression.take() else {
+            return;
+        };
+
+        match compression.join() {
+            Ok(Ok(())) => (),
+            Ok(Err(e)) => eprintln!("warning: {e}; the rotated file is left uncompressed"),
+            Err(_) => eprintln!("warning: the compression of a rotated file panicked"),
+        }
+    }
+}
+
+fn create_file(path: &Path) -> Result<BufWriter<File>> {
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|e| anyhow!("failed to create `{}`: {e}", path.display()))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(anyhow!("failed to remove `{}`: {e}", path.display())),
+        _ => Ok(()),
+    }
+}
+
+fn rename_if_exists(from: &Path, to: &Path) -> Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(anyhow!("failed to rename `{}`: {e}", from.display())),
+        _ => Ok(()),
+    }
+}
+
+/// Compresses `path` to `path.gz`, and removes it.
+fn compress(path: &Path) -> Result<()> {
+    let compressed = with_suffix(path, ".gz");
+    let compress = || -> io::Result<()> {
+        let mut encoder = GzEncoder::new(BufWriter::new(File::create(&compressed)?), Compression::default());
+        io::copy(&mut File::open(path)?, &mut encoder)?;
+        encoder.finish()?.flush()?;
+        fs::remove_file(path)
+    };
+    compress().map_err(|e| anyhow!("failed to compress `{}`: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, io::Read, process};
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_rotation() {
+        assert_eq!("100MB".parse::<Rotation>().unwrap(), Rotation::Size(100 << 20));
+        assert_eq!("512 kb".parse::<Rotation>().unwrap(), Rotation::Size(512 << 10));
+        assert_eq!(
+            "30m".parse::<Rotation>().unwrap(),
+            Rotation::Age(Duration::from_mins(30))
+        );
+        assert_eq!(
+            "1d".parse::<Rotation>().unwrap(),
+            Rotation::Age(Duration::from_hours(24))
+        );
+        assert!("0MB".parse::<Rotation>().is_err());
+        assert!("100M".parse::<Rotation>().is_err());
+        assert!("MB".parse::<Rotation>().is_err());
+    }
+
+    #[test]
+    fn test_rotate_files() {
+        let dir = env::temp_dir().join(format!("nyancat-rotate-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("saved.txt");
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+
+        let mut file = RotatingFile::create(&path, false)
+            .unwrap()
+            .with_rotation(Some(Rotation::Size(10)), 2, false);
+        for line in ["first", "second", "third", "fourth"] {
+            file.write_line(line).unwrap();
+        }
+        file.finish().unwrap();
+        assert_eq!(read("saved.txt"), "fourth\n");
+        assert_eq!(read("saved.txt.1"), "third\n");
+        assert_eq!(read("saved.txt.2"), "second\n");
+        assert!(!dir.join("saved.txt.3").exists());
+
+        let mut file = RotatingFile::create(&path, true)
+            .unwrap()
+            .with_rotation(Some(Rotation::Size(10)), 1, true);
+        for line in ["fifth", "sixth"] {
+            file.write_line(line).unwrap();
+        }
+        file.finish().unwrap();
+        let mut decompressed = String::new();
+        GzDecoder::new(File::open(dir.join("saved.txt.1.gz")).unwrap())
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, "fifth\n");
+        assert!(!dir.join("saved.txt.1").exists());
+        assert_eq!(read("saved.txt"), "sixth\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use anyhow::anyhow;
+use strum::{Display, EnumString};
+
+use super::Serializer;
+use crate::{
+    app::{ProcessNames, package},
+    log::{LogEntry, LogLine},
+    prelude::*,
+};
+
+/// The file of the index of the groups, in the split directory.
+const INDEX_FILE: &str = "index.tsv";
+
+/// The group entries are split by.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum SplitBy {
+    /// Each process, named by its process name when known, e.g. `com.example.app-1234`.
+    Pid,
+    /// All the processes of a package, e.g. `com.example.app` for `com.example.app:remote` too. Processes whose
+    /// name isn't known are grouped by pid.
+    Package,
+    Tag,
+    Level,
+    /// The buffer named by the last `--------- beginning of` header.
+    Buffer,
+}
+
+/// How the entries of groups are written.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum SplitFormat {
+    /// The `threadtime` format of logcat, in `.log` files.
+    #[default]
+    Logcat,
+    /// JSON Lines, in `.jsonl` files.
+    Jsonl,
+}
+
+impl SplitFormat {
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Logcat => "log",
+            Self::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// Writes the entries of each group into a file of its own, and an index of the groups with their line counts once
+/// the input ends.
+///
+/// Only the most recently written files are kept open; the others are closed, and appended to when their group
+/// logs again.
+pub struct Splitter {
+    split_by: SplitBy,
+    dir: PathBuf,
+    serializer: Serializer,
+    extension: &'static str,
+    process_names: Option<Rc<ProcessNames>>,
+    max_open_files: usize,
+    /// Whether lines are flushed as they're written, so that lines of live input are saved even if nyancat is
+    /// killed.
+    flush_lines: bool,
+    /// The buffer of the following entries.
+    buffer: Option<String>,
+    groups: HashMap<String, Group>,
+    file_names: HashSet<String>,
+    open_files: HashMap<String, BufWriter<File>>,
+    /// Counts writes, to find the least recently written file.
+    writes: u64,
+}
+
+struct Group {
+    name: String,
+    file_name: String,
+    lines: usize,
+    last_write: u64,
+}
+
+impl Splitter {
+    /// Creates a splitter writing the files of groups into `dir`, which is created if it doesn't exist.
+    pub fn new(split_by: SplitBy, dir: &Path, serializer: Serializer, format: SplitFormat) -> Result<Self> {
+        fs::create_dir_all(dir).map_err(|e| anyhow!("failed to create `{}`: {e}", dir.display()))?;
+        Ok(Self {
+            split_by,
+            dir: dir.to_owned(),
+            serializer,
+            extension: format.extension(),
+            process_names: None,
+            max_open_files: 64,
+            flush_lines: false,
+            buffer: None,
+            groups: HashMap::new(),
+            file_names: HashSet::new(),
+            open_files: HashMap::new(),
+            writes: 0,
+        })
+    }
+
+    /// Names the files of processes and packages by their process name, when known.
+    pub fn with_process_names(mut self, process_names: Option<Rc<ProcessNames>>) -> Self {
+        self.process_names = process_names;
+        self
+    }
+
+    pub fn with_max_open_files(mut self, max_open_files: usize) -> Self {
+        self.max_open_files = max_open_files.max(1);
+        self
+    }
+
+    pub const fn with_flush_lines(mut self, flush_lines: bool) -> Self {
+        self.flush_lines = flush_lines;
+        self
+    }
+
+    pub fn write(&mut self, line: &LogLine) -> Result<()> {
+        let entry = match line {
+            LogLine::Header { buffer } => {
+                self.buffer = Some(buffer.clone());
+                return Ok(());
+            },
+            LogLine::Entry(entry) => entry,
+        };
+
+        let key = self.group_key(entry);
+        if !self.groups.contains_key(&key) {
+            let group = self.new_group(entry);
+            self.groups.insert(key.clone(), group);
+        }
+        if !self.open_files.contains_key(&key) {
+            self.open_file(&key)?;
+        }
+
+        self.writes += 1;
+        let group = self
+            .groups
+            .get_mut(&key)
+            .expect("the group was just created");
+        group.lines += 1;
+        group.last_write = self.writes;
+        let file = self
+            .open_files
+            .get_mut(&key)
+            .expect("the file was just opened");
+        writeln!(file, "{}", self.serializer.serialize(line))
+            .and_then(|()| if self.flush_lines { file.flush() } else { Ok(()) })
+            .map_err(|e| anyhow!("failed to write to `{}`: {e}", group.file_name))
+    }
+
+    /// Flushes the files, and writes the index of the groups, from the one with the most lines.
+    pub fn finish(&mut self) -> Result<()> {
+        for (key, mut file) in self.open_files.drain() {
+            file.flush()
+                .map_err(|e| anyhow!("failed to write to `{}`: {e}", self.groups[&key].file_name))?;
+        }
+
+        let mut groups = self.groups.values().collect::<Vec<_>>();
+        groups.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.name.cmp(&b.name)));
+        let path = self.dir.join(INDEX_FILE);
+        let write_index = || -> io::Result<()> {
+            let mut index = BufWriter::new(File::create(&path)?);
+            writeln!(index, "{}\tfile\tlines", self.split_by)?;
+            for group in groups {
+                writeln!(index, "{}\t{}\t{}", group.name, group.file_name, group.lines)?;
+            }
+            index.flush()
+        };
+        write_index().map_err(|e| anyhow!("failed to write `{}`: {e}", path.display()))
+    }
+
+    fn group_key(&self, entry: &LogEntry) -> String {
+        match self.split_by {
+            SplitBy::Pid => entry.pid.to_string(),
+            SplitBy::Package => self
+                .process_name(entry.pid)
+                .map_or_else(|| format!("pid-{}", entry.pid), |name| package(&name).to_owned()),
+            SplitBy::Tag => entry.tag.clone(),
+            SplitBy::Level => format!("{:?}", entry.level).to_ascii_lowercase(),
+            SplitBy::Buffer => self.buffer.clone().unwrap_or_else(|| "unknown".to_owned()),
+        }
+    }
+
+    /// Creates the group of `entry`, named when it's first written to, with a file name of its own.
+    fn new_group(&mut self, entry: &LogEntry) -> Group {
+        let name = match self.split_by {
+            SplitBy::Pid => self
+                .process_name(entry.pid)
+                .map_or_else(|| format!("pid-{}", entry.pid), |name| format!("{name}-{}", entry.pid)),
+            _ => self.group_key(entry),
+        };
+
+        let stem = file_stem(&name);
+        let mut file_name = format!("{stem}.{}", self.extension);
+        for suffix in 2.. {
+            if self.file_names.insert(file_name.clone()) {
+                break;
+            }
+            file_name = format!("{stem}-{suffix}.{}", self.extension);
+        }
+
+        Group {
+            name,
+            file_name,
+            lines: 0,
+            last_write: 0,
+        }
+    }
+
+    /// Opens the file of the group, closing the least recently written file if too many are open. Files are
+    /// truncated when first opened, and appended to when reopened.
+    fn open_file(&mut self, key: &str) -> Result<()> {
+        if self.open_files.len() >= self.max_open_files
+            && let Some(oldest) = self
+                .open_files
+                .keys()
+                .min_by_key(|key| self.groups[*key].last_write)
+                .cloned()
+        {
+            let mut file = self.open_files.remove(&oldest).expect("the file is open");
+            file.flush()
+                .map_err(|e| anyhow!("failed to write to `{}`: {e}", self.groups[&oldest].file_name))?;
+        }
+
+        let group = &self.groups[key];
+        let path = self.dir.join(&group.file_name);
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(group.lines > 0)
+            .truncate(group.lines == 0)
+            .open(&path)
+            .map_err(|e| anyhow!("failed to open `{}`: {e}", path.display()))?;
+        self.open_files.insert(key.to_owned(), BufWriter::new(file));
+        Ok(())
+    }
+
+    fn process_name(&self, pid: u32) -> Option<String> {
+        self.process_names
+            .as_ref()
+            .and_then(|process_names| process_names.get(pid))
+    }
+}
+
+/// Replaces the characters of `name` that may not be valid in file names.
+fn file_stem(name: &str) -> String {
+    let stem = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    match stem.trim_start_matches('.') {
+        "" => "_".to_owned(),
+        stem => stem.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+    use crate::{output::LogcatWriter, zone::Zone};
+
+    #[test]
+    fn test_split_by_tag() {
+        let dir = env::temp_dir().join(format!("nyancat-split-{}", process::id()));
+        let serializer = Serializer::Logcat(LogcatWriter::new(Zone::Utc));
+        let mut splitter = Splitter::new(SplitBy::Tag, &dir, serializer, SplitFormat::Logcat)
+            .unwrap()
+            .with_max_open_files(1);
+        for line in [
+            LogLine::Entry(LogEntry::test("Finsky", "message").with_pid(1)),
+            LogLine::Entry(LogEntry::test("a/b", "message").with_pid(2)),
+            LogLine::Entry(LogEntry::test("a_b", "message").with_pid(3)),
+            LogLine::Entry(LogEntry::test("Finsky", "message").with_pid(4)),
+        ] {
+            splitter.write(&line).unwrap();
+        }
+        splitter.finish().unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("Finsky.log").lines().count(), 2);
+        assert!(read("a_b.log").contains("a/b"));
+        assert!(read("a_b-2.log").contains("a_b"));
+        assert_eq!(
+            read(INDEX_FILE),
+            "tag\tfile\tlines\nFinsky\tFinsky.log\t2\na/b\ta_b.log\t1\na_b\ta_b-2.log\t1\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_name_files() {
+        assert_eq!(file_stem("com.example.app-1234"), "com.example.app-1234");
+        assert_eq!(file_stem("../etc passwd"), "_etc_passwd");
+        assert_eq!(file_stem(".."), "_");
+    }
+}
@@ -0,0 +1,308 @@
+use std::{collections::HashMap, mem};
+
+use anyhow::anyhow;
+use csv::{ReaderBuilder, StringRecord};
+use lazy_regex::regex_is_match;
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+use time::{
+    Duration, OffsetDateTime, PrimitiveDateTime, UtcDateTime,
+    format_description::{BorrowedFormatItem, well_known::Rfc3339},
+};
+use time_macros::format_description;
+
+use super::MISSING_TAG;
+use crate::{
+    log::{LogEntry, LogLevel, LogLine},
+    prelude::*,
+    zone::Zone,
+};
+
+const DATETIME_FORMATS: [&[BorrowedFormatItem]; 2] = [
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond]"),
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second]"),
+];
+
+/// The fields of log entries, read from CSV columns.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Display, EnumString, EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum CsvField {
+    Datetime,
+    Pid,
+    Tid,
+    Level,
+    Tag,
+    Message,
+}
+
+impl CsvField {
+    /// The columns the field is read from unless it's mapped to another column, compared case insensitively.
+    const fn default_columns(self) -> &'static [&'static str] {
+        match self {
+            Self::Datetime => &["datetime", "time", "timestamp", "date"],
+            Self::Pid => &["pid"],
+            Self::Tid => &["tid"],
+            Self::Level => &["level", "priority"],
+            Self::Tag => &["tag"],
+            Self::Message => &["message", "msg"],
+        }
+    }
+
+    /// Entries can't do without a time and a message, but the other fields default to `0`, `info` and
+    /// `MISSING_TAG`.
+    const fn is_required(self) -> bool {
+        matches!(self, Self::Datetime | Self::Message)
+    }
+}
+
+/// Parses the `FIELD=COLUMN` mappings of `--csv-columns`.
+pub fn parse_csv_columns(mappings: &[String]) -> Result<HashMap<CsvField, String>> {
+    mappings
+        .iter()
+        .map(|mapping| {
+            let (field, column) = mapping
+                .split_once('=')
+                .ok_or_else(|| anyhow!("invalid CSV column mapping `{mapping}`, expected `FIELD=COLUMN`"))?;
+            let field = field
+                .trim()
+                .parse::<CsvField>()
+                .map_err(|_| anyhow!("unknown field `{field}` in CSV column mapping `{mapping}`"))?;
+            Ok((field, column.trim().to_owned()))
+        })
+        .collect()
+}
+
+/// Parses CSV exports of logcat output, whose first record is a header naming the columns.
+pub struct CsvParser {
+    /// The index of the column of each field.
+    indices: HashMap<CsvField, usize>,
+    /// The start of a record whose quoted value continues on the next line.
+    pending: String,
+    year: i32,
+    /// The time zone of timestamps without an offset.
+    source_zone: Zone,
+}
+
+impl CsvParser {
+    /// Creates a parser for the records following `header`, reading fields from the columns they're mapped to by
+    /// `columns`, or from the columns named by [`CsvField::default_columns`].
+    pub fn new(header: &str, columns: &HashMap<CsvField, String>) -> Result<Self> {
+        Self::with_year(header, columns, UtcDateTime::now().year())
+    }
+
+    /// Timestamps in the format of logcat don't include the year, so entries are assumed to be logged during `year`.
+    pub fn with_year(header: &str, columns: &HashMap<CsvField, String>, year: i32) -> Result<Self> {
+        Ok(Self {
+            indices: find_columns(&read_record(header)?, columns)?,
+            pending: String::new(),
+            year,
+            source_zone: Zone::Utc,
+        })
+    }
+
+    pub const fn set_source_zone(&mut self, source_zone: Zone) {
+        self.source_zone = source_zone;
+    }
+
+    /// Parses a line of the input, returning `None` for lines that don't complete a record.
+    pub fn parse_line(&mut self, line: &str) -> Result<Option<LogLine>> {
+        self.pending.push_str(line);
+        // Quotes are escaped by doubling them, so a record is complete when its quotes are balanced.
+        if self.pending.matches('"').count() % 2 == 1 {
+            self.pending.push('\n');
+            return Ok(None);
+        }
+
+        let record = read_record(&mem::take(&mut self.pending))?;
+        self.parse_entry(&record)
+            .map(|entry| Some(LogLine::Entry(entry)))
+    }
+
+    fn parse_entry(&self, record: &StringRecord) -> Result<LogEntry> {
+        let value = |field| {
+            self.indices
+                .get(&field)
+                .and_then(|&index| record.get(index))
+        };
+        let required = |field| value(field).ok_or_else(|| anyhow!("the CSV record has no value for {field}"));
+
+        let datetime = parse_datetime(required(CsvField::Datetime)?, self.year, self.source_zone)?;
+        let pid = value(CsvField::Pid).map(parse_id).transpose()?.unwrap_or(0);
+        let tid = value(CsvField::Tid).map(parse_id).transpose()?.unwrap_or(0);
+        let level = value(CsvField::Level)
+            .map(parse_level)
+            .transpose()?
+            .unwrap_or(LogLevel::Info);
+        let tag = value(CsvField::Tag)
+            .filter(|tag| !tag.is_empty())
+            .unwrap_or(MISSING_TAG);
+        let message = required(CsvField::Message)?;
+
+        Ok(LogEntry::new(
+            datetime,
+            pid,
+            tid,
+            level,
+            tag.trim().to_owned(),
+            message.trim_end().to_owned(),
+        ))
+    }
+}
+
+fn find_columns(header: &StringRecord, columns: &HashMap<CsvField, String>) -> Result<HashMap<CsvField, usize>> {
+    let mut indices = HashMap::new();
+    for field in CsvField::iter() {
+        let mapped = columns.get(&field);
+        let names = mapped.map_or_else(|| field.default_columns().to_vec(), |mapped| vec![mapped.as_str()]);
+        let index = header.iter().position(|column| {
+            names
+                .iter()
+                .any(|name| column.trim().eq_ignore_ascii_case(name))
+        });
+
+        match (index, mapped) {
+            (Some(index), _) => {
+                indices.insert(field, index);
+            },
+            (None, Some(mapped)) => return Err(anyhow!("the CSV header has no column `{mapped}` for {field}")),
+            (None, None) if field.is_required() => {
+                return Err(anyhow!(
+                    "the CSV header has no column for {field}, map one with `--csv-columns {field}=COLUMN`"
+                ));
+            },
+            (None, None) => (),
+        }
+    }
+    Ok(indices)
+}
+
+fn read_record(record: &str) -> Result<StringRecord> {
+    ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(record.as_bytes())
+        .records()
+        .next()
+        .unwrap_or_else(|| Ok(StringRecord::new()))
+        .map_err(|e| anyhow!("failed to parse CSV record: {e}"))
+}
+
+fn parse_id(id: &str) -> Result<u32> {
+    id.trim()
+        .parse()
+        .map_err(|e| anyhow!("invalid pid or tid `{id}` in CSV record: {e}"))
+}
+
+/// Parses a level written as in logcat (`I`), by name (`info`), or as the priority of Android's `Log` (`4`).
+fn parse_level(level: &str) -> Result<LogLevel> {
+    match level.trim().to_ascii_lowercase().as_str() {
+        "s" | "silent" => Ok(LogLevel::Silent),
+        "v" | "verbose" | "2" => Ok(LogLevel::Verbose),
+        "d" | "debug" | "3" => Ok(LogLevel::Debug),
+        "i" | "info" | "4" => Ok(LogLevel::Info),
+        "w" | "warn" | "warning" | "5" => Ok(LogLevel::Warning),
+        "e" | "error" | "6" => Ok(LogLevel::Error),
+        "f" | "fatal" | "a" | "assert" | "7" => Ok(LogLevel::Fatal),
+        _ => Err(anyhow!("unknown level `{level}` in CSV record")),
+    }
+}
+
+/// Parses a datetime in RFC 3339, as `YYYY-MM-DD HH:MM:SS[.SSS]`, in the format of logcat, or as a Unix timestamp in
+/// seconds, or in milliseconds if it has more than 11 digits. Datetimes without an offset are in `source_zone`.
+fn parse_datetime(datetime: &str, year: i32, source_zone: Zone) -> Result<UtcDateTime> {
+    let datetime = datetime.trim();
+    if let Ok(datetime) = OffsetDateTime::parse(datetime, &Rfc3339) {
+        return Ok(datetime.into());
+    }
+
+    if regex_is_match!(r"^\d+(\.\d+)?$", datetime) {
+        let timestamp = if !datetime.contains('.') && datetime.len() > 11 {
+            datetime.parse::<i64>().map(Duration::milliseconds).ok()
+        } else {
+            datetime.parse::<f64>().map(Duration::seconds_f64).ok()
+        };
+        return timestamp
+            .map(|timestamp| UtcDateTime::UNIX_EPOCH + timestamp)
+            .ok_or_else(|| anyhow!("invalid timestamp `{datetime}` in CSV record"));
+    }
+
+    // Logcat prints datetimes without the year, e.g. `10-01 12:10:36.746`.
+    let datetime_with_year = if regex_is_match!(r"^\d{2}-\d{2} ", datetime) {
+        format!("{year}-{datetime}")
+    } else {
+        datetime.replacen('T', " ", 1)
+    };
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| PrimitiveDateTime::parse(&datetime_with_year, format).ok())
+        .map(|datetime| source_zone.to_utc(datetime))
+        .ok_or_else(|| anyhow!("invalid datetime `{datetime}` in CSV record"))
+}
+
+#[cfg(test)]
+mod tests {
+    use time_macros::utc_datetime;
+
+    use super::*;
+
+    fn parse(columns: &HashMap<CsvField, String>, lines: &[&str]) -> Vec<LogLine> {
+        let mut parser = CsvParser::with_year(lines[0], columns, 2025).unwrap();
+        lines[1..]
+            .iter()
+            .filter_map(|line| parser.parse_line(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let lines = parse(
+            &HashMap::new(),
+            &[
+                "Timestamp,PID,TID,Priority,Tag,Message",
+                "2025-10-01T12:10:36.746Z,14344,14372,4,Finsky,\"jobFinished, \"\"10-71\"\"\"",
+                "1759320637000,1,1,E,,\"first",
+                "second\"",
+            ],
+        );
+        assert_eq!(
+            lines,
+            [
+                LogLine::entry(
+                    utc_datetime!(2025-10-01 12:10:36.746),
+                    14344,
+                    14372,
+                    LogLevel::Info,
+                    "Finsky".to_owned(),
+                    "jobFinished, \"10-71\"".to_owned(),
+                ),
+                LogLine::entry(
+                    utc_datetime!(2025-10-01 12:10:37),
+                    1,
+                    1,
+                    LogLevel::Error,
+                    MISSING_TAG.to_owned(),
+                    "first\nsecond".to_owned(),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_with_mapped_columns() {
+        let columns = parse_csv_columns(&["datetime=logged_at".to_owned(), "message = text".to_owned()]).unwrap();
+        let lines = parse(&columns, &["logged_at,text,severity", "10-01 12:10:36.746,hello,warn"]);
+        assert_eq!(
+            lines,
+            [LogLine::entry(
+                utc_datetime!(2025-10-01 12:10:36.746),
+                0,
+                0,
+                LogLevel::Info,
+                MISSING_TAG.to_owned(),
+                "hello".to_owned(),
+            )]
+        );
+
+        assert!(parse_csv_columns(&["color=c".to_owned()]).is_err());
+        assert!(CsvParser::with_year("logged_at,text", &HashMap::new(), 2025).is_err());
+        assert!(CsvParser::with_year("logged_at,text", &columns, 2025).is_ok());
+    }
+}
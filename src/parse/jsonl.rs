@@ -0,0 +1,50 @@
+use anyhow::anyhow;
+
+use crate::{log::LogLine, prelude::*};
+
+/// Parses a line written by `--output=jsonl`. Fields other than those of [`LogLine`], like `process`, are ignored.
+pub fn parse_line(line: &str) -> Result<LogLine> {
+    serde_json::from_str(line).map_err(|e| anyhow!("failed to parse JSON line: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use time_macros::utc_datetime;
+
+    use super::*;
+    use crate::{log::LogLevel, output::JsonlWriter};
+
+    #[test]
+    fn test_parse_written_lines() {
+        let writer = JsonlWriter::new(None, Some("emulator-5554".to_owned()), "logcat.txt".to_owned());
+        let lines = [
+            LogLine::header("main"),
+            LogLine::entry(
+                utc_datetime!(2025-10-01 12:10:36.746),
+                14344,
+                14372,
+                LogLevel::Info,
+                "Finsky".to_owned(),
+                "[2] SCH: jobFinished".to_owned(),
+            ),
+        ];
+        for line in lines {
+            assert_eq!(parse_line(&writer.to_json(&line)).unwrap(), line);
+        }
+
+        assert_eq!(
+            parse_line(r#"{"type":"entry","datetime":"2025-10-01T14:10:36+02:00","pid":1,"tid":1,"level":"E","tag":"T","message":"m"}"#)
+                .unwrap(),
+            LogLine::entry(
+                utc_datetime!(2025-10-01 12:10:36),
+                1,
+                1,
+                LogLevel::Error,
+                "T".to_owned(),
+                "m".to_owned()
+            )
+        );
+        assert!(parse_line(r#"{"type":"entry","pid":1}"#).is_err());
+        assert!(parse_line("10-01 12:10:36.746 1 1 I T: m").is_err());
+    }
+}
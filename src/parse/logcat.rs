@@ -2,12 +2,13 @@ use std::str::FromStr;
 
 use anyhow::anyhow;
 use chumsky::{prelude::*, text::Char};
-use time::{UtcDateTime, format_description::BorrowedFormatItem};
+use time::{PrimitiveDateTime, UtcDateTime, UtcOffset, format_description::BorrowedFormatItem};
 use time_macros::format_description;
 
 use crate::{
     log::{LogLevel, LogLine},
     prelude::*,
+    zone::{self, Zone},
 };
 
 const DATETIME_FORMAT: &[BorrowedFormatItem] =
@@ -17,17 +18,35 @@ pub const MISSING_TAG: &str = "MISSING_TAG";
 
 type ParserErr<'src> = extra::Err<Rich<'src, char>>;
 
-pub struct LogcatParser;
+pub struct LogcatParser {
+    year: i32,
+    /// The time zone of timestamps printed without an offset.
+    source_zone: Zone,
+}
 
 impl LogcatParser {
-    pub const fn new() -> Self {
-        Self
+    pub fn new() -> Self {
+        Self::with_year(UtcDateTime::now().year())
+    }
+
+    /// Logcat doesn't print the year, so entries are assumed to be logged during `year`.
+    pub const fn with_year(year: i32) -> Self {
+        Self {
+            year,
+            source_zone: Zone::Utc,
+        }
+    }
+
+    /// Converts timestamps printed without an offset, i.e. without the `zone` modifier of logcat, from `source_zone`
+    /// to UTC.
+    pub const fn set_source_zone(&mut self, source_zone: Zone) {
+        self.source_zone = source_zone;
     }
 
     pub fn parse_log_line(&self, line: &str) -> Result<LogLine> {
         let line_end = end().or(text::newline().then_ignore(end()));
         let parser = logcat_header_parser()
-            .or(log_entry_parser())
+            .or(log_entry_parser(self.year, self.source_zone))
             .then_ignore(line_end);
 
         parser.parse(line).into_result().map_err(|errors| {
@@ -49,8 +68,8 @@ fn logcat_header_parser<'src>() -> impl Parser<'src, &'src str, LogLine, ParserE
         .map(LogLine::header)
 }
 
-fn log_entry_parser<'src>() -> impl Parser<'src, &'src str, LogLine, ParserErr<'src>> {
-    datetime_parser()
+fn log_entry_parser<'src>(year: i32, source_zone: Zone) -> impl Parser<'src, &'src str, LogLine, ParserErr<'src>> {
+    datetime_parser(year, source_zone)
         .then(u32_parser().padded())
         .then(u32_parser().padded())
         .then(level_parser().padded())
@@ -60,7 +79,7 @@ fn log_entry_parser<'src>() -> impl Parser<'src, &'src str, LogLine, ParserErr<'
         })
 }
 
-fn datetime_parser<'src>() -> impl Parser<'src, &'src str, UtcDateTime, ParserErr<'src>> {
+fn datetime_parser<'src>(year: i32, source_zone: Zone) -> impl Parser<'src, &'src str, UtcDateTime, ParserErr<'src>> {
     let digits = |c| text::digits(10).exactly(c);
     digits(2) // mm
         .then_ignore(just('-'))
@@ -75,12 +94,30 @@ fn datetime_parser<'src>() -> impl Parser<'src, &'src str, UtcDateTime, ParserEr
         .then(digits(3)) // mmm
         .then_ignore(just(' '))
         .to_slice()
-        .try_map(|input, span| parse_datetime(input).map_err(|e| Rich::custom(span, e)))
+        .try_map(move |input, span| parse_datetime(input, year).map_err(|e| Rich::custom(span, e)))
+        .then(offset_parser().or_not())
+        .map(move |(datetime, offset)| {
+            offset.map_or_else(
+                || source_zone.to_utc(datetime),
+                |offset| datetime.assume_offset(offset).to_utc(),
+            )
+        })
+}
+
+/// Parses the offset printed after the time by the `zone` modifier of logcat, e.g. `+0200`.
+fn offset_parser<'src>() -> impl Parser<'src, &'src str, UtcOffset, ParserErr<'src>> {
+    one_of("+-")
+        .then(text::digits(10).exactly(4))
+        .to_slice()
+        .then_ignore(just(' '))
+        .try_map(|offset, span| {
+            zone::parse_offset(offset).ok_or_else(|| Rich::custom(span, format!("invalid offset `{offset}`")))
+        })
 }
 
-fn parse_datetime(input: &str) -> Result<UtcDateTime> {
-    let input = format!("{}-{input}", UtcDateTime::now().date().year());
-    let result = UtcDateTime::parse(&input, DATETIME_FORMAT)
+fn parse_datetime(input: &str, year: i32) -> Result<PrimitiveDateTime> {
+    let input = format!("{year}-{input}");
+    let result = PrimitiveDateTime::parse(&input, DATETIME_FORMAT)
         .map_err(|e| anyhow!("unexpected error while parsing datetime: {e}"))?;
 
     Ok(result)
@@ -119,7 +156,7 @@ mod tests {
     fn test_parse_logcat_header() {
         let test_case = |header: &str| (format!("--------- beginning of {header}"), LogLine::header(header));
         let headers = vec!["main", "system", "radio"];
-        let parser = LogcatParser::new();
+        let parser = LogcatParser::with_year(2025);
 
         for header in headers {
             let (line, expected) = test_case(header);
@@ -132,7 +169,7 @@ mod tests {
     #[test]
     fn test_parse_log_line_sanity() {
         let line = r"10-01 12:10:45.848  1515  1971 I MiuiNetworkPolicy: removeUidState uid = 10147";
-        let result = LogcatParser::new().parse_log_line(line).unwrap();
+        let result = LogcatParser::with_year(2025).parse_log_line(line).unwrap();
 
         let expected = LogLine::entry(
             utc_datetime!(2025-10-01 12:10:45.848),
@@ -149,7 +186,7 @@ mod tests {
     #[test]
     fn test_parse_log_line_with_missing_tag() {
         let line = r"10-01 12:10:45.588 14344 14376 E Finsky [89] AU2 RequiredVehicleState is missing.";
-        let result = LogcatParser::new().parse_log_line(line).unwrap();
+        let result = LogcatParser::with_year(2025).parse_log_line(line).unwrap();
 
         let expected = LogLine::entry(
             utc_datetime!(2025-10-01 12:10:45.588),
@@ -166,7 +203,7 @@ mod tests {
     #[test]
     fn test_parse_log_line_with_padded_tag() {
         let line = r"10-01 12:10:45.588 14344 14376 E Finsky  : [89] AU2: RequiredVehicleState is missing.";
-        let result = LogcatParser::new().parse_log_line(line).unwrap();
+        let result = LogcatParser::with_year(2025).parse_log_line(line).unwrap();
 
         let expected = LogLine::entry(
             utc_datetime!(2025-10-01 12:10:45.588),
@@ -183,7 +220,7 @@ mod tests {
     #[test]
     fn test_parse_log_line_with_dots_in_tag_and_long_message() {
         let line = r"10-01 12:10:45.853 11472 11487 I com.xiaomi.xmsf: oneway function results for code 3 on binder at 0xb400007d87527a00 will be dropped but finished with status UNKNOWN_TRANSACTION";
-        let result = LogcatParser::new().parse_log_line(line).unwrap();
+        let result = LogcatParser::with_year(2025).parse_log_line(line).unwrap();
 
         let expected = LogLine::entry(
             utc_datetime!(2025-10-01 12:10:45.853),
@@ -200,7 +237,7 @@ mod tests {
     #[test]
     fn test_parse_log_line_with_colons_in_tag_and_complex_message() {
         let line = r#"10-01 12:10:45.813  4375  4375 W binder:4375_3: type=1400 audit(0.0:1427): avc: denied { read } for name="u:object_r:system_adbd_prop:s0" dev="tmpfs" ino=1260 scontext=u:r:gmscore_app:s0:c512,c768 tcontext=u:object_r:system_adbd_prop:s0 tclass=file permissive=0 app=com.google.android.gms"#;
-        let result = LogcatParser::new().parse_log_line(line).unwrap();
+        let result = LogcatParser::with_year(2025).parse_log_line(line).unwrap();
 
         let expected = LogLine::entry(
             utc_datetime!(2025-10-01 12:10:45.813),
@@ -214,6 +251,27 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_parse_log_line_with_zone() {
+        let mut parser = LogcatParser::with_year(2025);
+        parser.set_source_zone("Europe/Berlin".parse().unwrap());
+        let expected = LogLine::entry(
+            utc_datetime!(2025-10-01 12:10:45.848),
+            1515,
+            1971,
+            LogLevel::Info,
+            "MiuiNetworkPolicy".to_owned(),
+            "removeUidState uid = 10147".to_owned(),
+        );
+
+        let local_line = r"10-01 14:10:45.848  1515  1971 I MiuiNetworkPolicy: removeUidState uid = 10147";
+        assert_eq!(parser.parse_log_line(local_line).unwrap(), expected);
+
+        // The offset printed by the zone modifier takes precedence over the source zone.
+        let zoned_line = r"10-01 05:10:45.848 -0700  1515  1971 I MiuiNetworkPolicy: removeUidState uid = 10147";
+        assert_eq!(parser.parse_log_line(zoned_line).unwrap(), expected);
+    }
+
     #[test]
     fn test_parse_log_line_with_all_log_levels() {
         let test_case = |log_level: LogLevel| {
@@ -229,7 +287,7 @@ mod tests {
                 ),
             )
         };
-        let parser = LogcatParser::new();
+        let parser = LogcatParser::with_year(2025);
 
         for log_level in LogLevel::iter() {
             let (line, expected) = test_case(log_level);
@@ -249,7 +307,7 @@ mod tests {
             "10-01 12:10:45.100 1000",                         // missing fields
             "10-01 12:10:45.100 1000 1000 X TestTag: message", // invalid level
         ];
-        let parser = LogcatParser::new();
+        let parser = LogcatParser::with_year(2025);
 
         for line in invalid_lines {
             let result = parser.parse_log_line(line);
@@ -0,0 +1,128 @@
+mod csv;
+mod jsonl;
+mod logcat;
+
+use strum::{Display, EnumString};
+
+pub use csv::{CsvParser, parse_csv_columns};
+pub use logcat::{LogcatParser, MISSING_TAG};
+
+use crate::{
+    log::{LogEntry, LogLine},
+    prelude::*,
+    zone::Zone,
+};
+
+/// The format of the input.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum InputFormat {
+    /// JSON Lines if the first line is a JSON object, and logcat output otherwise.
+    #[default]
+    Auto,
+    Logcat,
+    /// The JSON Lines written by `--output=jsonl`.
+    Jsonl,
+    Csv,
+}
+
+impl InputFormat {
+    /// Resolves [`Self::Auto`] to the format of the input starting with `first_line`.
+    pub fn detect(self, first_line: &str) -> Self {
+        match self {
+            Self::Auto if first_line.trim_start().starts_with('{') => Self::Jsonl,
+            Self::Auto => Self::Logcat,
+            format => format,
+        }
+    }
+}
+
+/// Parses lines of input in any of the supported formats.
+pub enum InputParser {
+    Logcat(LogcatParser),
+    Jsonl,
+    Csv(CsvParser),
+}
+
+impl InputParser {
+    /// Converts timestamps without an offset from `source_zone` to UTC, see [`LogcatParser::set_source_zone`].
+    pub const fn set_source_zone(&mut self, source_zone: Zone) {
+        match self {
+            Self::Logcat(parser) => parser.set_source_zone(source_zone),
+            // Serialized datetimes always have an offset.
+            Self::Jsonl => (),
+            Self::Csv(parser) => parser.set_source_zone(source_zone),
+        }
+    }
+
+    /// Parses a line of input into the log lines it holds: several if the message of an entry spans multiple lines,
+    /// and none if the line doesn't complete a CSV record.
+    pub fn parse_line(&mut self, line: &str) -> Result<Vec<LogLine>> {
+        match self {
+            Self::Logcat(parser) => parser.parse_log_line(line).map(|log_line| vec![log_line]),
+            Self::Jsonl => jsonl::parse_line(line).map(split_message_lines),
+            Self::Csv(parser) => parser
+                .parse_line(line)
+                .map(|log_line| log_line.map(split_message_lines).unwrap_or_default()),
+        }
+    }
+}
+
+/// Splits an entry whose message spans multiple lines into an entry for each line, as logcat prints them.
+fn split_message_lines(log_line: LogLine) -> Vec<LogLine> {
+    match log_line {
+        LogLine::Entry(entry) if entry.message.contains('\n') => entry
+            .message
+            .lines()
+            .map(|message| {
+                LogLine::Entry(LogEntry {
+                    message: message.to_owned(),
+                    ..entry.clone()
+                })
+            })
+            .collect(),
+        log_line => vec![log_line],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time_macros::utc_datetime;
+
+    use super::*;
+    use crate::log::LogLevel;
+
+    #[test]
+    fn test_detect_input_format() {
+        assert_eq!(
+            InputFormat::Auto.detect(r#"{"type":"header","buffer":"main"}"#),
+            InputFormat::Jsonl
+        );
+        assert_eq!(
+            InputFormat::Auto.detect("--------- beginning of main"),
+            InputFormat::Logcat
+        );
+        assert_eq!(InputFormat::Csv.detect("{"), InputFormat::Csv);
+    }
+
+    #[test]
+    fn test_split_multiline_messages() {
+        let mut parser = InputParser::Jsonl;
+        let lines = parser
+            .parse_line(
+                r#"{"type":"entry","datetime":"2025-10-01T12:10:36.746Z","pid":1,"tid":2,"level":"warning","tag":"Finsky","message":"first\nsecond"}"#,
+            )
+            .unwrap();
+        assert_eq!(
+            lines,
+            ["first", "second"].map(|message| LogLine::entry(
+                utc_datetime!(2025-10-01 12:10:36.746),
+                1,
+                2,
+                LogLevel::Warning,
+                "Finsky".to_owned(),
+                message.to_owned(),
+            ))
+        );
+    }
+}
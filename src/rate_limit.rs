@@ -0,0 +1,157 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::anyhow;
+use regex::Regex;
+use time::{Duration, UtcDateTime};
+
+use crate::{filter::MatchOptions, log::LogEntry, prelude::*};
+
+/// A limit on how many lines each tag matching a regex may log per time unit.
+pub struct RateLimit {
+    tag: Regex,
+    max_lines: usize,
+    period: Duration,
+}
+
+impl RateLimit {
+    /// Parses a limit of the form `TAG=N/s` or `TAG=N/m`, where `TAG` is matched like tag filters.
+    pub fn new(rule: &str, options: MatchOptions) -> Result<Self> {
+        let (tag, rate) = rule
+            .rsplit_once('=')
+            .ok_or_else(|| anyhow!("invalid rate limit `{rule}`: expected `TAG=N/s`"))?;
+        let (max_lines, unit) = rate.split_once('/').unwrap_or((rate, "s"));
+
+        let max_lines = max_lines
+            .trim()
+            .parse()
+            .map_err(|e| anyhow!("invalid rate limit `{rule}`: {e}"))?;
+        let period = match unit.trim() {
+            "s" => Duration::SECOND,
+            "m" => Duration::MINUTE,
+            unit => {
+                return Err(anyhow!(
+                    "invalid rate limit `{rule}`: unknown unit `{unit}`, expected `s` or `m`"
+                ));
+            },
+        };
+        let tag = options.build_whole_regex(tag)?;
+
+        Ok(Self { tag, max_lines, period })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Admission {
+    /// The entry is within the limit; `suppressed` lines of its tag were dropped since the last admitted one.
+    Admitted {
+        suppressed: usize,
+    },
+    Suppressed,
+}
+
+#[derive(Default)]
+struct TagWindow {
+    recent: VecDeque<UtcDateTime>,
+    suppressed: usize,
+}
+
+/// Drops entries of tags that log faster than their [`RateLimit`], counting how many were dropped.
+///
+/// Rates are measured using the entries' timestamps, so files are limited the same way as live input.
+pub struct RateLimiter {
+    limits: Vec<RateLimit>,
+    windows: HashMap<String, TagWindow>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: Vec<RateLimit>) -> Self {
+        Self {
+            limits,
+            windows: HashMap::new(),
+        }
+    }
+
+    pub fn admit(&mut self, entry: &LogEntry) -> Admission {
+        let Some(limit) = self
+            .limits
+            .iter()
+            .find(|limit| limit.tag.is_match(&entry.tag))
+        else {
+            return Admission::Admitted { suppressed: 0 };
+        };
+
+        let window = self.windows.entry(entry.tag.clone()).or_default();
+        while let Some(oldest) = window.recent.front()
+            && entry.datetime - *oldest >= limit.period
+        {
+            window.recent.pop_front();
+        }
+
+        if window.recent.len() >= limit.max_lines {
+            window.suppressed += 1;
+            return Admission::Suppressed;
+        }
+
+        window.recent.push_back(entry.datetime);
+        Admission::Admitted {
+            suppressed: std::mem::take(&mut window.suppressed),
+        }
+    }
+
+    /// Returns how many lines of each tag were dropped since the last admitted one, sorted by tag.
+    pub fn finish(&mut self) -> Vec<(String, usize)> {
+        let mut suppressed = self
+            .windows
+            .drain()
+            .filter(|(_, window)| window.suppressed > 0)
+            .map(|(tag, window)| (tag, window.suppressed))
+            .collect::<Vec<_>>();
+        suppressed.sort();
+        suppressed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rate_limit() {
+        assert!(RateLimit::new("FileUtils=10/s", MatchOptions::default()).is_ok());
+        assert!(RateLimit::new("File.*=10/m", MatchOptions::default()).is_ok());
+        assert!(RateLimit::new("FileUtils=10", MatchOptions::default()).is_ok());
+        assert!(RateLimit::new("FileUtils", MatchOptions::default()).is_err());
+        assert!(RateLimit::new("FileUtils=ten/s", MatchOptions::default()).is_err());
+        assert!(RateLimit::new("FileUtils=10/h", MatchOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_suppress_lines_exceeding_rate() {
+        let mut limiter = RateLimiter::new(vec![RateLimit::new("FileUtils=2/s", MatchOptions::default()).unwrap()]);
+        let entry = |millisecond: i64, tag: &str| {
+            LogEntry::test(tag, "err write to mi_exception_log").after(Duration::milliseconds(millisecond))
+        };
+
+        assert_eq!(
+            limiter.admit(&entry(0, "FileUtils")),
+            Admission::Admitted { suppressed: 0 }
+        );
+        assert_eq!(
+            limiter.admit(&entry(100, "FileUtils")),
+            Admission::Admitted { suppressed: 0 }
+        );
+        assert_eq!(limiter.admit(&entry(200, "FileUtils")), Admission::Suppressed);
+        assert_eq!(limiter.admit(&entry(300, "FileUtils")), Admission::Suppressed);
+        assert_eq!(
+            limiter.admit(&entry(400, "Other")),
+            Admission::Admitted { suppressed: 0 }
+        );
+        assert_eq!(
+            limiter.admit(&entry(1000, "FileUtils")),
+            Admission::Admitted { suppressed: 2 }
+        );
+        assert_eq!(limiter.admit(&entry(1050, "FileUtils")), Admission::Suppressed);
+
+        assert_eq!(limiter.finish(), vec![("FileUtils".to_owned(), 1)]);
+    }
+}
@@ -0,0 +1,415 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::anyhow;
+use lazy_regex::{regex, regex_captures};
+use serde_json::Value;
+
+use crate::{
+    log::LogEntry,
+    prelude::*,
+    stacktrace::{Frame, StacktraceLine},
+};
+
+/// The original names of the classes and methods of an app, read from the `mapping.txt` written by R8 or `ProGuard`.
+#[derive(Debug, Default)]
+pub struct Mapping {
+    /// The classes by their obfuscated name.
+    classes: HashMap<String, ClassMapping>,
+    /// The source files of classes by their original name, where R8 recorded them.
+    source_files: HashMap<String, String>,
+}
+
+#[derive(Debug)]
+struct ClassMapping {
+    original: String,
+    /// The methods by their obfuscated name, in the order of the mapping file.
+    methods: HashMap<String, Vec<MethodMapping>>,
+}
+
+/// A range of lines of an obfuscated method, mapped to a method it was compiled from.
+///
+/// When methods are inlined, the same obfuscated range is mapped to several methods: first the innermost inlined
+/// method, then each method that called it, and last the method that was compiled.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct MethodMapping {
+    obfuscated_range: Option<(u32, u32)>,
+    /// The class the method was declared in, if it was inlined from another class.
+    original_class: Option<String>,
+    original_name: String,
+    /// The first and, unless the method is only known to be called from the first line, the last original line.
+    original_range: Option<(u32, Option<u32>)>,
+}
+
+impl MethodMapping {
+    fn original_line(&self, line: u32) -> u32 {
+        match (self.obfuscated_range, self.original_range) {
+            (Some((start, end)), Some((original_start, Some(original_end))))
+                if original_end.checked_sub(original_start) == end.checked_sub(start) =>
+            {
+                original_start + line.saturating_sub(start)
+            },
+            (_, Some((original_start, _))) => original_start,
+            _ => line,
+        }
+    }
+}
+
+impl ClassMapping {
+    /// Returns the methods a frame of the obfuscated method `name` at `line` stands for, innermost first.
+    fn frames(&self, name: &str, line: Option<u32>) -> Vec<&MethodMapping> {
+        let Some(methods) = self.methods.get(name) else {
+            return Vec::new();
+        };
+
+        if let Some(line) = line {
+            let inlined = methods
+                .iter()
+                .filter(|method| {
+                    method
+                        .obfuscated_range
+                        .is_some_and(|(start, end)| (start..=end).contains(&line))
+                })
+                .collect::<Vec<_>>();
+            if !inlined.is_empty() {
+                return inlined;
+            }
+        }
+
+        // Without a line, the inlined methods can't be told apart, so only the compiled method is known.
+        methods
+            .iter()
+            .find(|method| method.obfuscated_range.is_none())
+            .or_else(|| {
+                let first = methods.first()?;
+                methods
+                    .iter()
+                    .take_while(|method| method.obfuscated_range == first.obfuscated_range)
+                    .last()
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+impl Mapping {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).map_err(|e| anyhow!("failed to read mapping file `{}`: {e}", path.display()))?;
+        Self::parse(&content).map_err(|e| anyhow!("invalid mapping file `{}`: {e}", path.display()))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let mut mapping = Self::default();
+        let mut current_class: Option<(String, ClassMapping)> = None;
+
+        for (number, line) in content.lines().enumerate() {
+            if let Some(comment) = line.trim_start().strip_prefix('#') {
+                if let Some((_, class)) = &current_class
+                    && let Some(source_file) = parse_source_file(comment)
+                {
+                    mapping
+                        .source_files
+                        .insert(class.original.clone(), source_file);
+                }
+                continue;
+            }
+
+            if let Some((_, original, obfuscated)) = regex_captures!(r"^(\S+) -> (\S+):$", line) {
+                mapping.insert(current_class.take());
+                current_class = Some((
+                    obfuscated.to_owned(),
+                    ClassMapping {
+                        original: original.to_owned(),
+                        methods: HashMap::new(),
+                    },
+                ));
+                continue;
+            }
+
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some((_, class)) = &mut current_class else {
+                return Err(anyhow!("line {}: expected a class mapping, found `{line}`", number + 1));
+            };
+            // Fields are mapped too, but never appear in stack traces.
+            if let Some((obfuscated, method)) = parse_method(line) {
+                class
+                    .methods
+                    .entry(obfuscated.to_owned())
+                    .or_default()
+                    .push(method);
+            }
+        }
+        mapping.insert(current_class);
+
+        Ok(mapping)
+    }
+
+    fn insert(&mut self, class: Option<(String, ClassMapping)>) {
+        if let Some((obfuscated, class)) = class {
+            self.classes.insert(obfuscated, class);
+        }
+    }
+
+    /// Returns the source file of the class `original`, as recorded by R8, or as named by Java conventions.
+    fn source_file(&self, original: &str) -> String {
+        self.source_files.get(original).cloned().unwrap_or_else(|| {
+            let simple_name = original.rsplit('.').next().unwrap_or(original);
+            let outer_class = simple_name.split('$').next().unwrap_or(simple_name);
+            format!("{outer_class}.java")
+        })
+    }
+}
+
+/// Parses a method line, e.g. `1:5:void com.example.Util.log(int):10:14 -> a`, returning its obfuscated name.
+fn parse_method(line: &str) -> Option<(&str, MethodMapping)> {
+    let (_, start, end, class, name, original_start, original_end, obfuscated) = regex_captures!(
+        r"^\s+(?:(\d+):(\d+):)?\S+ (?:([^\s(]+)\.)?([^\s.(]+)\([^)]*\)(?::(\d+)(?::(\d+))?)? -> (\S+)$",
+        line
+    )?;
+    let number = |capture: &str| capture.parse::<u32>().ok();
+    Some((
+        obfuscated,
+        MethodMapping {
+            obfuscated_range: number(start).zip(number(end)),
+            original_class: (!class.is_empty()).then(|| class.to_owned()),
+            original_name: name.to_owned(),
+            original_range: number(original_start).map(|original_start| (original_start, number(original_end))),
+        },
+    ))
+}
+
+/// Parses the `# {"id":"sourceFile","fileName":"Example.kt"}` metadata R8 writes after a class.
+fn parse_source_file(comment: &str) -> Option<String> {
+    let metadata = serde_json::from_str::<Value>(comment.trim()).ok()?;
+    if metadata.get("id")?.as_str()? != "sourceFile" {
+        return None;
+    }
+    metadata.get("fileName")?.as_str().map(str::to_owned)
+}
+
+/// Rewrites obfuscated names in stack traces back to their original names, using the mappings of one or more apps.
+pub struct Retracer {
+    mappings: Vec<Mapping>,
+}
+
+impl Retracer {
+    pub const fn new(mappings: Vec<Mapping>) -> Self {
+        Self { mappings }
+    }
+
+    /// Returns `entry` with its obfuscated names rewritten. A frame of a method other methods were inlined into is
+    /// expanded into a frame for each of them, so several entries may be returned.
+    pub fn retrace(&self, entry: &LogEntry) -> Vec<LogEntry> {
+        let messages = match StacktraceLine::parse(&entry.message) {
+            Some(StacktraceLine::Frame(frame)) => self.retrace_frame(&entry.message, frame),
+            Some(StacktraceLine::Continuation { .. }) => None,
+            _ => self
+                .retrace_message(&entry.message)
+                .map(|message| vec![message]),
+        };
+
+        messages.map_or_else(
+            || vec![entry.clone()],
+            |messages| {
+                messages
+                    .into_iter()
+                    .map(|message| LogEntry {
+                        message,
+                        ..entry.clone()
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn find_class(&self, obfuscated: &str) -> Option<(&Mapping, &ClassMapping)> {
+        self.mappings.iter().find_map(|mapping| {
+            mapping
+                .classes
+                .get(obfuscated)
+                .map(|class| (mapping, class))
+        })
+    }
+
+    fn retrace_frame(&self, message: &str, frame: Frame) -> Option<Vec<String>> {
+        let (module, package) = frame
+            .package
+            .rsplit_once('/')
+            .map_or(("", frame.package), |(module, package)| (module, package));
+        let module = if module.is_empty() {
+            String::new()
+        } else {
+            format!("{module}/")
+        };
+        let (mapping, class) = self.find_class(&format!("{package}{}", frame.class))?;
+
+        let line = frame
+            .location
+            .rsplit_once(':')
+            .and_then(|(_, line)| line.parse::<u32>().ok());
+        let indent = &message[..message.len() - message.trim_start().len()];
+        let location = |original_class: &str, original_line: Option<u32>| match original_line {
+            Some(original_line) => format!("{}:{original_line}", mapping.source_file(original_class)),
+            None if matches!(frame.location, "Native Method" | "Unknown Source") => frame.location.to_owned(),
+            None => mapping.source_file(original_class),
+        };
+
+        let methods = class.frames(frame.method, line);
+        if methods.is_empty() {
+            return Some(vec![format!(
+                "{indent}at {module}{}.{}({})",
+                class.original,
+                frame.method,
+                location(&class.original, line)
+            )]);
+        }
+
+        let frames = methods
+            .into_iter()
+            .map(|method| {
+                let original_class = method.original_class.as_deref().unwrap_or(&class.original);
+                let original_line = line.map(|line| method.original_line(line));
+                format!(
+                    "{indent}at {module}{original_class}.{}({})",
+                    method.original_name,
+                    location(original_class, original_line)
+                )
+            })
+            .collect();
+        Some(frames)
+    }
+
+    /// Rewrites the obfuscated class of an exception, and obfuscated class names in its message.
+    ///
+    /// As short obfuscated names like `a` are also common words, names in messages are only rewritten if they include
+    /// their package or are printed by `Object.toString()`, e.g. `ehtt@6d1d2b6`.
+    fn retrace_message(&self, message: &str) -> Option<String> {
+        let exception_start = regex_captures!(r"^(\s*(?:(?:Caused by|Suppressed): )?)[\w$.]+(?::|$)", message)
+            .map(|(_, prefix)| prefix.len());
+
+        let mut retraced = false;
+        let rewritten =
+            regex!(r"[A-Za-z_$][\w$]*(?:\.[A-Za-z_$][\w$]*)*").replace_all(message, |captures: &regex::Captures| {
+                let name = captures.get(0).expect("the whole match is always captured");
+                let is_unambiguous = name.as_str().contains('.')
+                    || message[name.end()..].starts_with('@')
+                    || exception_start == Some(name.start());
+                match self.find_class(name.as_str()) {
+                    Some((_, class)) if is_unambiguous => {
+                        retraced = true;
+                        class.original.clone()
+                    },
+                    _ => name.as_str().to_owned(),
+                }
+            });
+
+        retraced.then(|| rewritten.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAPPING: &str = "\
+# compiler: R8
+com.example.rcs.EventService -> ehtr:
+# {\"id\":\"sourceFile\",\"fileName\":\"EventService.kt\"}
+    android.content.Context context -> a
+    1:4:void unbind():40:43 -> h
+    5:5:void com.example.rcs.Binder.release(int):12:12 -> h
+    5:5:void unbind():44 -> h
+    6:7:void unbind():45:45 -> h
+    void disconnect() -> disconnect
+com.example.rcs.EventService$Connection -> ehtt:
+    1:1:void <init>():7:7 -> <init>
+com.example.rcs.ServiceNotBoundException -> a.b:
+";
+
+    fn retracer() -> Retracer {
+        Retracer::new(vec![Mapping::parse(MAPPING).unwrap()])
+    }
+
+    fn messages(retracer: &Retracer, message: &str) -> Vec<String> {
+        let entry = LogEntry::test("RcsClientLib", message);
+        retracer
+            .retrace(&entry)
+            .into_iter()
+            .map(|entry| entry.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_mapping() {
+        let mapping = Mapping::parse(MAPPING).unwrap();
+        let class = &mapping.classes["ehtr"];
+        assert_eq!(class.original, "com.example.rcs.EventService");
+        assert_eq!(class.methods["h"].len(), 4);
+        assert_eq!(
+            class.methods["h"][1],
+            MethodMapping {
+                obfuscated_range: Some((5, 5)),
+                original_class: Some("com.example.rcs.Binder".to_owned()),
+                original_name: "release".to_owned(),
+                original_range: Some((12, Some(12))),
+            }
+        );
+        assert_eq!(mapping.source_file("com.example.rcs.EventService"), "EventService.kt");
+        assert_eq!(
+            mapping.source_file("com.example.rcs.EventService$Connection"),
+            "EventService.java"
+        );
+
+        assert!(Mapping::parse("    void run() -> a").is_err());
+    }
+
+    #[test]
+    fn test_retrace_frames() {
+        let retracer = retracer();
+
+        assert_eq!(
+            messages(&retracer, "\tat ehtr.h(PG:3)"),
+            ["\tat com.example.rcs.EventService.unbind(EventService.kt:42)"]
+        );
+        assert_eq!(
+            messages(&retracer, "\tat ehtr.h(PG:5)"),
+            [
+                "\tat com.example.rcs.Binder.release(Binder.java:12)",
+                "\tat com.example.rcs.EventService.unbind(EventService.kt:44)",
+            ]
+        );
+        assert_eq!(
+            messages(&retracer, "\tat ehtr.h(PG:7)"),
+            ["\tat com.example.rcs.EventService.unbind(EventService.kt:45)"]
+        );
+        assert_eq!(
+            messages(&retracer, "\tat ehtr.disconnect(Unknown Source)"),
+            ["\tat com.example.rcs.EventService.disconnect(Unknown Source)"]
+        );
+        assert_eq!(
+            messages(
+                &retracer,
+                "\tat android.app.LoadedApk.forgetServiceDispatcher(LoadedApk.java:2042)"
+            ),
+            ["\tat android.app.LoadedApk.forgetServiceDispatcher(LoadedApk.java:2042)"]
+        );
+    }
+
+    #[test]
+    fn test_retrace_exceptions() {
+        let retracer = retracer();
+
+        assert_eq!(
+            messages(&retracer, "Caused by: a.b: Service not registered: ehtt@6d1d2b6"),
+            [
+                "Caused by: com.example.rcs.ServiceNotBoundException: Service not registered: \
+                 com.example.rcs.EventService$Connection@6d1d2b6"
+            ]
+        );
+        assert_eq!(messages(&retracer, "ehtr"), ["com.example.rcs.EventService"]);
+        // Short names are also words.
+        assert_eq!(messages(&retracer, "failed to bind ehtr"), ["failed to bind ehtr"]);
+    }
+}
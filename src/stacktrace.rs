@@ -0,0 +1,435 @@
+use std::mem;
+
+use lazy_regex::{Lazy, regex, regex_captures};
+use regex::Regex;
+
+use crate::log::LogEntry;
+
+const STACKTRACE_ENTRY_REGEX: &Lazy<Regex> = regex!(r"^\s+((at)|(\.{3})) ");
+const STACKTRACE_CAUSE_REGEX: &Lazy<Regex> = regex!(r"^\s*(Caused by|Suppressed): ");
+
+/// Packages of the Android framework, the JDK and Kotlin, whose frames are collapsed.
+pub const FRAMEWORK_PACKAGES: [&str; 11] = [
+    "android",
+    "androidx",
+    "com.android.internal",
+    "dalvik",
+    "java",
+    "javax",
+    "jdk",
+    "kotlin",
+    "kotlinx.coroutines",
+    "libcore",
+    "sun",
+];
+
+pub fn is_stacktrace_entry(entry: &LogEntry) -> bool {
+    STACKTRACE_ENTRY_REGEX.is_match(&entry.message)
+}
+
+/// Returns whether `entry` is a `Caused by:` or `Suppressed:` line.
+pub fn is_stacktrace_cause(entry: &LogEntry) -> bool {
+    STACKTRACE_CAUSE_REGEX.is_match(&entry.message)
+}
+
+/// A line of a Java or Kotlin stack trace.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StacktraceLine<'a> {
+    Exception(Exception<'a>),
+    Frame(Frame<'a>),
+    /// A `... 13 more` line, standing for the frames shared with the enclosing trace, or a frame that couldn't be
+    /// split into its parts.
+    Continuation {
+        nesting: usize,
+        text: &'a str,
+    },
+}
+
+impl<'a> StacktraceLine<'a> {
+    pub fn parse(message: &'a str) -> Option<Self> {
+        if let Some(frame) = Frame::parse(message) {
+            return Some(Self::Frame(frame));
+        }
+        if STACKTRACE_ENTRY_REGEX.is_match(message) {
+            return Some(Self::Continuation {
+                nesting: frame_nesting(message),
+                text: message.trim_start(),
+            });
+        }
+        Exception::parse(message).map(Self::Exception)
+    }
+
+    /// Returns how many `Suppressed:` exceptions the line is nested in.
+    pub const fn nesting(&self) -> usize {
+        match self {
+            Self::Exception(exception) => exception.nesting,
+            Self::Frame(frame) => frame.nesting,
+            Self::Continuation { nesting, .. } => *nesting,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Relation {
+    /// The exception the stack trace is printed for.
+    Thrown,
+    CausedBy,
+    Suppressed,
+}
+
+impl Relation {
+    pub const fn label(self) -> Option<&'static str> {
+        match self {
+            Self::Thrown => None,
+            Self::CausedBy => Some("Caused by: "),
+            Self::Suppressed => Some("Suppressed: "),
+        }
+    }
+}
+
+/// The first line of a stack trace, e.g. `java.lang.IllegalStateException: message`, or a `Caused by:` or
+/// `Suppressed:` line.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Exception<'a> {
+    pub relation: Relation,
+    pub nesting: usize,
+    pub class: &'a str,
+    pub message: Option<&'a str>,
+}
+
+impl<'a> Exception<'a> {
+    fn parse(message: &'a str) -> Option<Self> {
+        if let Some((_, indent, relation, class, _, message)) =
+            regex_captures!(r"^(\s*)(Caused by|Suppressed): (\S+?)(: (.*))?$", message)
+        {
+            return Some(Self {
+                relation: if relation == "Suppressed" {
+                    Relation::Suppressed
+                } else {
+                    Relation::CausedBy
+                },
+                nesting: indent.matches('\t').count(),
+                class,
+                message: (!message.is_empty()).then_some(message),
+            });
+        }
+
+        // Thrown exceptions are only recognized by their name, as anything could precede a stack trace.
+        let (_, class, _, message) = regex_captures!(
+            r"^((?:[A-Za-z_$][\w$]*\.)+[A-Z][\w$]*(?:Exception|Error|Throwable))(: (.*))?$",
+            message
+        )?;
+        Some(Self {
+            relation: Relation::Thrown,
+            nesting: 0,
+            class,
+            message: (!message.is_empty()).then_some(message),
+        })
+    }
+}
+
+/// A frame of a stack trace, e.g. `at android.app.LoadedApk.forgetServiceDispatcher(LoadedApk.java:2042)`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Frame<'a> {
+    pub nesting: usize,
+    /// The module and package of the class, including the trailing dot, e.g. `java.base/java.lang.`.
+    pub package: &'a str,
+    pub class: &'a str,
+    pub method: &'a str,
+    /// The file and line, `Native Method` or `Unknown Source`, without parentheses.
+    pub location: &'a str,
+}
+
+impl<'a> Frame<'a> {
+    fn parse(message: &'a str) -> Option<Self> {
+        let (_, package, class, method, location) = regex_captures!(
+            r"^\s+at ((?:[\w$.@]+/)?(?:[\w$]+\.)*)([\w$]+)\.([\w$<>-]+)\((.*)\)$",
+            message
+        )?;
+        Some(Self {
+            nesting: frame_nesting(message),
+            package,
+            class,
+            method,
+            location,
+        })
+    }
+
+    /// Returns whether the frame's class is in any of `packages`, or their subpackages. Packages can be written
+    /// `com.example` or `com.example.*`.
+    pub fn is_in<S: AsRef<str>>(&self, packages: &[S]) -> bool {
+        let package = self.package.rsplit('/').next().unwrap_or_default();
+        let package = package.strip_suffix('.').unwrap_or(package);
+        packages.iter().any(|parent| {
+            let parent = parent.as_ref().trim_end_matches(".*");
+            package
+                .strip_prefix(parent)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    }
+}
+
+/// Frames are indented with one more tab than the exception they belong to.
+fn frame_nesting(message: &str) -> usize {
+    let indent = &message[..message.len() - message.trim_start().len()];
+    indent.matches('\t').count().saturating_sub(1)
+}
+
+/// Keeps track of how `Caused by:` and `Suppressed:` exceptions are nested, to lay them out as a tree.
+#[derive(Debug, Default)]
+pub struct ExceptionTree {
+    /// The depth of the last exception seen at each level of nesting.
+    depths: Vec<usize>,
+}
+
+impl ExceptionTree {
+    /// Returns the depth of the exception `line` belongs to. The thrown exception is at depth 0, and each
+    /// `Caused by:` or `Suppressed:` exception is one deeper than the exception it belongs to.
+    pub fn observe(&mut self, line: &StacktraceLine) -> usize {
+        let nesting = line.nesting();
+        let StacktraceLine::Exception(exception) = line else {
+            return self.depth_at(nesting);
+        };
+
+        let depth = match exception.relation {
+            Relation::Thrown => 0,
+            // Each cause is printed after the frames of the exception it caused.
+            Relation::CausedBy => self.depth_at(nesting) + 1,
+            Relation::Suppressed => self.depth_at(nesting.saturating_sub(1)) + 1,
+        };
+        self.depths.truncate(nesting);
+        self.depths.resize(nesting + 1, depth);
+        depth
+    }
+
+    fn depth_at(&self, nesting: usize) -> usize {
+        self.depths
+            .get(nesting)
+            .or_else(|| self.depths.last())
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// An entry to print, or a run of framework frames replaced by a single line.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FoldedEntry {
+    Entry(LogEntry),
+    CollapsedFrames { first: LogEntry, count: usize },
+}
+
+/// Collapses runs of consecutive framework frames logged by the same thread.
+///
+/// Framework frames are only returned once a different entry arrives, or the input ends.
+pub struct FrameCollapser {
+    /// The shortest run of frames that is collapsed.
+    min_run: usize,
+    /// Packages whose frames are never collapsed, even if they're in a framework package.
+    my_code: Vec<String>,
+    run: Vec<LogEntry>,
+}
+
+impl FrameCollapser {
+    pub const fn new(min_run: usize, my_code: Vec<String>) -> Self {
+        Self {
+            min_run,
+            my_code,
+            run: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, entry: LogEntry) -> Vec<FoldedEntry> {
+        let is_framework_frame = matches!(
+            StacktraceLine::parse(&entry.message),
+            Some(StacktraceLine::Frame(frame)) if frame.is_in(&FRAMEWORK_PACKAGES) && !frame.is_in(&self.my_code)
+        );
+        let continues_run = self
+            .run
+            .first()
+            .is_none_or(|first| first.pid == entry.pid && first.tid == entry.tid);
+
+        if is_framework_frame && continues_run {
+            self.run.push(entry);
+            return Vec::new();
+        }
+
+        let mut folded = self.finish();
+        if is_framework_frame {
+            self.run.push(entry);
+        } else {
+            folded.push(FoldedEntry::Entry(entry));
+        }
+        folded
+    }
+
+    pub fn finish(&mut self) -> Vec<FoldedEntry> {
+        let run = mem::take(&mut self.run);
+        if run.len() < self.min_run {
+            return run.into_iter().map(FoldedEntry::Entry).collect();
+        }
+
+        let count = run.len();
+        let first = run
+            .into_iter()
+            .next()
+            .expect("runs that are long enough are not empty");
+        vec![FoldedEntry::CollapsedFrames { first, count }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stacktrace_lines() {
+        assert_eq!(
+            StacktraceLine::parse("java.lang.IllegalArgumentException: Service not registered: ehtt@6d1d2b6"),
+            Some(StacktraceLine::Exception(Exception {
+                relation: Relation::Thrown,
+                nesting: 0,
+                class: "java.lang.IllegalArgumentException",
+                message: Some("Service not registered: ehtt@6d1d2b6"),
+            }))
+        );
+        assert_eq!(
+            StacktraceLine::parse("\tSuppressed: java.io.IOException"),
+            Some(StacktraceLine::Exception(Exception {
+                relation: Relation::Suppressed,
+                nesting: 1,
+                class: "java.io.IOException",
+                message: None,
+            }))
+        );
+        assert_eq!(
+            StacktraceLine::parse("\tat android.app.LoadedApk.forgetServiceDispatcher(LoadedApk.java:2042)"),
+            Some(StacktraceLine::Frame(Frame {
+                nesting: 0,
+                package: "android.app.",
+                class: "LoadedApk",
+                method: "forgetServiceDispatcher",
+                location: "LoadedApk.java:2042",
+            }))
+        );
+        assert!(matches!(
+            StacktraceLine::parse("\t\tat java.base/java.lang.Thread.run(Native Method)"),
+            Some(StacktraceLine::Frame(Frame {
+                nesting: 1,
+                package: "java.base/java.lang.",
+                method: "run",
+                ..
+            }))
+        ));
+        assert!(matches!(
+            StacktraceLine::parse("\tat com.example.Rcs.$r8$lambda$FWM-xeGripR0_dMeQQlFKKAds0A(PG:120)"),
+            Some(StacktraceLine::Frame(Frame {
+                class: "Rcs",
+                method: "$r8$lambda$FWM-xeGripR0_dMeQQlFKKAds0A",
+                ..
+            }))
+        ));
+        assert_eq!(
+            StacktraceLine::parse("\t... 13 more"),
+            Some(StacktraceLine::Continuation {
+                nesting: 0,
+                text: "... 13 more",
+            })
+        );
+        assert_eq!(StacktraceLine::parse("Service not registered: ehtt@6d1d2b6"), None);
+    }
+
+    #[test]
+    fn test_frame_packages() {
+        let Some(StacktraceLine::Frame(frame)) =
+            StacktraceLine::parse("\tat kotlinx.coroutines.internal.DispatchedTask.run(DispatchedTask.kt:108)")
+        else {
+            panic!("Expected a frame");
+        };
+
+        assert!(frame.is_in(&FRAMEWORK_PACKAGES));
+        assert!(frame.is_in(&["kotlinx.coroutines.*"]));
+        assert!(!frame.is_in(&["kotlinx.co"]));
+        assert!(!frame.is_in(&["com.example"]));
+    }
+
+    #[test]
+    fn test_nest_exceptions() {
+        let lines = [
+            "java.lang.RuntimeException: outer",
+            "\tat com.example.Main.main(Main.java:1)",
+            "\tSuppressed: java.io.IOException: close",
+            "\t\tat com.example.Main.close(Main.java:2)",
+            "\tCaused by: java.io.EOFException",
+            "\t\tat com.example.Main.read(Main.java:3)",
+            "Caused by: java.lang.IllegalStateException: inner",
+            "\tat com.example.Main.init(Main.java:4)",
+            "Caused by: java.lang.NullPointerException",
+            "\t... 2 more",
+        ];
+        let mut tree = ExceptionTree::default();
+
+        let depths = lines
+            .iter()
+            .map(|line| tree.observe(&StacktraceLine::parse(line).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(depths, vec![0, 0, 1, 1, 2, 2, 1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn test_collapse_framework_frames() {
+        let mut collapser = FrameCollapser::new(2, vec!["android.app".to_owned()]);
+        let messages = [
+            "java.lang.IllegalArgumentException: Service not registered",
+            "\tat android.app.LoadedApk.forgetServiceDispatcher(LoadedApk.java:2042)",
+            "\tat android.content.ContextWrapper.unbindService(ContextWrapper.java:899)",
+            "\tat java.util.concurrent.Executors$RunnableAdapter.call(Executors.java:487)",
+            "\tat java.lang.Thread.run(Thread.java:1012)",
+            "\tat ehtr.h(PG:8)",
+            "\tat java.lang.Thread.run(Thread.java:1012)",
+        ];
+
+        let mut folded = messages
+            .iter()
+            .flat_map(|message| collapser.push(LogEntry::test("RcsClientLib", message).with_tid(13763)))
+            .collect::<Vec<_>>();
+        folded.extend(collapser.finish());
+
+        let summary = folded
+            .iter()
+            .map(|folded| match folded {
+                FoldedEntry::Entry(entry) => (entry.message.as_str(), 1),
+                FoldedEntry::CollapsedFrames { first, count } => (first.message.as_str(), *count),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (messages[0], 1),
+                (messages[1], 1),
+                (messages[2], 3),
+                (messages[5], 1),
+                (messages[6], 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collapse_frames_of_each_thread_separately() {
+        let mut collapser = FrameCollapser::new(2, Vec::new());
+        let frame = "\tat java.lang.Thread.run(Thread.java:1012)";
+
+        assert!(
+            collapser
+                .push(LogEntry::test("RcsClientLib", frame).with_tid(1))
+                .is_empty()
+        );
+        assert_eq!(
+            collapser.push(LogEntry::test("RcsClientLib", frame).with_tid(2)),
+            vec![FoldedEntry::Entry(LogEntry::test("RcsClientLib", frame).with_tid(1))]
+        );
+        assert_eq!(
+            collapser.finish(),
+            vec![FoldedEntry::Entry(LogEntry::test("RcsClientLib", frame).with_tid(2))]
+        );
+    }
+}
@@ -0,0 +1,512 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    rc::Rc,
+};
+
+use serde_json::{Value, json};
+use strum::{Display, EnumString, IntoEnumIterator};
+use time::{Duration, UtcDateTime, format_description::BorrowedFormatItem, format_description::well_known::Rfc3339};
+use time_macros::format_description;
+
+use crate::{
+    app::{ProcessNames, package},
+    crash::CrashKind,
+    format::Theme,
+    log::{LogEntry, LogLevel},
+    zone::Zone,
+};
+
+const DATETIME_FORMAT: &[BorrowedFormatItem] = format_description!("[month]-[day] [hour]:[minute]:[second]");
+const MINUTE_FORMAT: &[BorrowedFormatItem] = format_description!("[month]-[day] [hour]:[minute]");
+
+/// The number of rows of each ranking of the table, the others being summed up in a last row.
+const TOP_ROWS: usize = 10;
+
+/// The number of the busiest minutes in the table.
+const BUSIEST_MINUTES: usize = 5;
+
+/// The kinds of crashes, in the order they're summed up.
+const CRASH_KINDS: [(CrashKind, &str); 4] = [
+    (CrashKind::Java, "java"),
+    (CrashKind::Native, "native"),
+    (CrashKind::Anr, "anr"),
+    (CrashKind::ForceFinishing, "force_finishing"),
+];
+
+/// How the summary of the statistics is printed.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum StatsFormat {
+    /// Tables colored with the theme.
+    #[default]
+    Table,
+    /// A JSON object, with every tag, process and minute rather than the top ones.
+    Json,
+}
+
+/// Counts the entries of the input by level, tag, process and minute, along with the crashes they report and the
+/// lines that couldn't be parsed.
+pub struct Stats {
+    process_names: Option<Rc<ProcessNames>>,
+    entries: usize,
+    parse_failures: usize,
+    first: Option<UtcDateTime>,
+    last: Option<UtcDateTime>,
+    levels: BTreeMap<LogLevel, usize>,
+    tags: HashMap<String, usize>,
+    pids: HashMap<u32, usize>,
+    /// The entries of each minute, by its start.
+    minutes: BTreeMap<UtcDateTime, usize>,
+    /// The entries of the second being counted, by its start.
+    second: Option<(UtcDateTime, usize)>,
+    /// The second with the most entries so far.
+    peak_second: Option<(UtcDateTime, usize)>,
+    crashes: HashMap<CrashKind, usize>,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self {
+            process_names: None,
+            entries: 0,
+            parse_failures: 0,
+            first: None,
+            last: None,
+            levels: BTreeMap::new(),
+            tags: HashMap::new(),
+            pids: HashMap::new(),
+            minutes: BTreeMap::new(),
+            second: None,
+            peak_second: None,
+            crashes: HashMap::new(),
+        }
+    }
+
+    /// Names processes, and groups them by package, with the names known once the summary is printed.
+    pub fn with_process_names(mut self, process_names: Option<Rc<ProcessNames>>) -> Self {
+        self.process_names = process_names;
+        self
+    }
+
+    pub fn observe(&mut self, entry: &LogEntry) {
+        self.entries += 1;
+        self.first = Some(
+            self.first
+                .map_or(entry.datetime, |first| first.min(entry.datetime)),
+        );
+        self.last = Some(
+            self.last
+                .map_or(entry.datetime, |last| last.max(entry.datetime)),
+        );
+        *self.levels.entry(entry.level).or_default() += 1;
+        match self.tags.get_mut(&entry.tag) {
+            Some(count) => *count += 1,
+            None => {
+                self.tags.insert(entry.tag.clone(), 1);
+            },
+        }
+        *self.pids.entry(entry.pid).or_default() += 1;
+        *self
+            .minutes
+            .entry(truncate(entry.datetime, 60))
+            .or_default() += 1;
+
+        // Seconds are counted one at a time rather than kept, as entries arrive about in order.
+        let second = truncate(entry.datetime, 1);
+        let count = match &mut self.second {
+            Some((current, count)) if *current == second => {
+                *count += 1;
+                *count
+            },
+            _ => {
+                self.second = Some((second, 1));
+                1
+            },
+        };
+        if self.peak_second.is_none_or(|(_, peak)| count > peak) {
+            self.peak_second = Some((second, count));
+        }
+
+        if let Some(kind) = CrashKind::detect(entry) {
+            *self.crashes.entry(kind).or_default() += 1;
+        }
+    }
+
+    /// Counts a line of the input that couldn't be parsed.
+    pub const fn parse_failure(&mut self) {
+        self.parse_failures += 1;
+    }
+
+    pub fn summary(&self, format: StatsFormat, theme: &Theme, zone: Zone) -> String {
+        match format {
+            StatsFormat::Table => self.to_table(theme, zone),
+            StatsFormat::Json => self.to_json(),
+        }
+    }
+
+    /// Returns the summary as tables, with the top rows of rankings, and times in `zone`.
+    pub fn to_table(&self, theme: &Theme, zone: Zone) -> String {
+        Table {
+            stats: self,
+            theme,
+            zone,
+        }
+        .to_string()
+    }
+
+    /// Returns the summary as a JSON object, with every tag, process, package and minute, from the one with the most
+    /// entries, and times in RFC 3339.
+    pub fn to_json(&self) -> String {
+        let levels = LogLevel::iter()
+            .map(|level| {
+                let count = self.levels.get(&level).copied().unwrap_or_default();
+                (format!("{level:?}").to_ascii_lowercase(), json!(count))
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let tags = ranked(self.tags.iter().map(|(tag, &count)| (tag.as_str(), count)))
+            .into_iter()
+            .map(|(tag, count)| json!({"tag": tag, "entries": count}))
+            .collect::<Vec<_>>();
+        let processes = self
+            .processes()
+            .into_iter()
+            .map(|(pid, process, count)| {
+                let package = process.as_deref().map(package);
+                json!({"pid": pid, "process": process, "package": package, "entries": count})
+            })
+            .collect::<Vec<_>>();
+        let packages = self
+            .packages()
+            .into_iter()
+            .map(|(package, count)| json!({"package": package, "entries": count}))
+            .collect::<Vec<_>>();
+        let minutes = self
+            .minutes
+            .iter()
+            .map(|(&minute, &count)| json!({"minute": rfc3339(minute), "entries": count}))
+            .collect::<Vec<_>>();
+        let crashes = CRASH_KINDS
+            .iter()
+            .map(|(kind, name)| {
+                (
+                    (*name).to_owned(),
+                    json!(self.crashes.get(kind).copied().unwrap_or_default()),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        json!({
+            "entries": self.entries,
+            "parse_failures": self.parse_failures,
+            "first": self.first.map(rfc3339),
+            "last": self.last.map(rfc3339),
+            "levels": levels,
+            "tags": tags,
+            "processes": processes,
+            "packages": packages,
+            "minutes": minutes,
+            "peak_second": self
+                .peak_second
+                .map(|(second, count)| json!({"second": rfc3339(second), "entries": count})),
+            "average_rate": self.average_rate(),
+            "crashes": crashes,
+        })
+        .to_string()
+    }
+
+    /// Returns the pids with their process name, if known, and their entries, from the one with the most entries.
+    fn processes(&self) -> Vec<(u32, Option<String>, usize)> {
+        let mut processes = self
+            .pids
+            .iter()
+            .map(|(&pid, &count)| (pid, self.process_name(pid), count))
+            .collect::<Vec<_>>();
+        processes.sort_by_key(|&(pid, _, count)| (Reverse(count), pid));
+        processes
+    }
+
+    /// Returns the packages of the processes whose name is known, with their entries, from the one with the most.
+    fn packages(&self) -> Vec<(String, usize)> {
+        let mut packages = HashMap::<String, usize>::new();
+        for (&pid, &count) in &self.pids {
+            if let Some(name) = self.process_name(pid) {
+                *packages.entry(package(&name).to_owned()).or_default() += count;
+            }
+        }
+        ranked(packages)
+    }
+
+    fn process_name(&self, pid: u32) -> Option<String> {
+        self.process_names
+            .as_ref()
+            .and_then(|process_names| process_names.get(pid))
+    }
+
+    /// Returns the entries per second, over the time between the first and last entries.
+    fn average_rate(&self) -> f64 {
+        let (Some(first), Some(last)) = (self.first, self.last) else {
+            return 0.0;
+        };
+        #[allow(clippy::cast_precision_loss)]
+        let entries = self.entries as f64;
+        entries / (last - first).as_seconds_f64().max(1.0)
+    }
+}
+
+/// The summary as tables, see [`Stats::to_table`].
+struct Table<'a> {
+    stats: &'a Stats,
+    theme: &'a Theme,
+    zone: Zone,
+}
+
+impl fmt::Display for Table<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { stats, theme, zone } = *self;
+        let format_time = |datetime: UtcDateTime, format| {
+            zone.localize(datetime)
+                .format(format)
+                .unwrap_or_else(|e| panic!("failed to format datetime: {e}"))
+        };
+        let width = separated(stats.entries).len();
+        let row = |count: usize| {
+            format!(
+                "  {:>width$} {:>6}",
+                separated(count),
+                format!("{:.1}%", percentage(count, stats.entries))
+            )
+        };
+
+        write!(
+            f,
+            "{} {} entries",
+            theme.header.apply("Statistics:"),
+            separated(stats.entries)
+        )?;
+        if let (Some(first), Some(last)) = (stats.first, stats.last) {
+            write!(
+                f,
+                " from {} to {} ({})",
+                theme.datetime.apply(format_time(first, DATETIME_FORMAT)),
+                theme.datetime.apply(format_time(last, DATETIME_FORMAT)),
+                format_duration(last - first)
+            )?;
+        }
+        writeln!(f)?;
+
+        writeln!(f, "\n{}", theme.header.apply("Levels"))?;
+        for (&level, &count) in &stats.levels {
+            let badge = theme.level(level).badge.apply(format!(" {level} "));
+            writeln!(f, "{}  {badge}", row(count))?;
+        }
+
+        writeln!(f, "\n{}", theme.header.apply("Tags"))?;
+        let tags = ranked(stats.tags.iter().map(|(tag, &count)| (tag.as_str(), count)));
+        for &(tag, count) in tags.iter().take(TOP_ROWS) {
+            writeln!(f, "{}  {}", row(count), theme.tag.apply(tag))?;
+        }
+        write_others(f, &tags, "tags", row)?;
+
+        writeln!(f, "\n{}", theme.header.apply("Processes"))?;
+        let processes = stats.processes();
+        for &(pid, ref name, count) in processes.iter().take(TOP_ROWS) {
+            let pid = theme.pid.apply(format!("{pid:>5}"));
+            match name {
+                Some(name) => writeln!(f, "{}  {pid} {name}", row(count)),
+                None => writeln!(f, "{}  {pid}", row(count)),
+            }?;
+        }
+        let counts = processes
+            .iter()
+            .map(|&(_, _, count)| ((), count))
+            .collect::<Vec<_>>();
+        write_others(f, &counts, "processes", row)?;
+
+        let packages = stats.packages();
+        if !packages.is_empty() {
+            writeln!(f, "\n{}", theme.header.apply("Packages"))?;
+            for (package, count) in packages.iter().take(TOP_ROWS) {
+                writeln!(f, "{}  {package}", row(*count))?;
+            }
+            write_others(f, &packages, "packages", row)?;
+        }
+
+        writeln!(f, "\n{}", theme.header.apply("Busiest minutes"))?;
+        let minutes = ranked(
+            stats
+                .minutes
+                .iter()
+                .map(|(&minute, &count)| (minute, count)),
+        );
+        for &(minute, count) in minutes.iter().take(BUSIEST_MINUTES) {
+            let minute = theme.datetime.apply(format_time(minute, MINUTE_FORMAT));
+            writeln!(f, "{}  {minute}", row(count))?;
+        }
+        if let Some((second, count)) = stats.peak_second {
+            writeln!(
+                f,
+                "  Peak of {} entries/s at {}, {:.1} entries/s on average",
+                separated(count),
+                theme.datetime.apply(format_time(second, DATETIME_FORMAT)),
+                stats.average_rate()
+            )?;
+        }
+
+        writeln!(f, "\n{}", theme.header.apply("Crashes"))?;
+        for (kind, _) in CRASH_KINDS {
+            let count = stats.crashes.get(&kind).copied().unwrap_or_default();
+            let kind = kind.to_string();
+            let kind = if count > 0 {
+                theme.crash.apply(kind)
+            } else {
+                kind.into()
+            };
+            writeln!(f, "  {:>width$}  {kind}", separated(count))?;
+        }
+        if stats.parse_failures > 0 {
+            let failures = format!("{} lines couldn't be parsed", separated(stats.parse_failures));
+            writeln!(f, "\n{}", theme.level(LogLevel::Warning).message.apply(failures))?;
+        }
+        Ok(())
+    }
+}
+
+/// Sorts `counts` from the most to the least, and then by key.
+fn ranked<K: Ord>(counts: impl IntoIterator<Item = (K, usize)>) -> Vec<(K, usize)> {
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Writes a row summing up the counts beyond the top rows, if any.
+fn write_others<K>(
+    f: &mut fmt::Formatter<'_>,
+    counts: &[(K, usize)],
+    name: &str,
+    row: impl Fn(usize) -> String,
+) -> fmt::Result {
+    let Some(others) = counts.get(TOP_ROWS..).filter(|others| !others.is_empty()) else {
+        return Ok(());
+    };
+    let count = others.iter().map(|(_, count)| count).sum();
+    writeln!(f, "{}  {} other {name}", row(count), others.len())
+}
+
+fn truncate(datetime: UtcDateTime, seconds: i64) -> UtcDateTime {
+    let timestamp = datetime.unix_timestamp();
+    UtcDateTime::from_unix_timestamp(timestamp - timestamp.rem_euclid(seconds)).unwrap_or(datetime)
+}
+
+fn rfc3339(datetime: UtcDateTime) -> Value {
+    datetime.format(&Rfc3339).map_or(Value::Null, Value::String)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn percentage(count: usize, total: usize) -> f64 {
+    100.0 * count as f64 / total.max(1) as f64
+}
+
+/// Formats `count` with thousands separators, e.g. `12,345`.
+fn separated(count: usize) -> String {
+    let digits = count.to_string();
+    let mut separated = String::with_capacity(digits.len() * 4 / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            separated.push(',');
+        }
+        separated.push(digit);
+    }
+    separated
+}
+
+/// Formats `duration` in its two largest units, e.g. `4m 26s` or `2h 5m`.
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.whole_seconds();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time_macros::utc_datetime;
+
+    use super::*;
+
+    fn stats() -> Stats {
+        let process_names = ProcessNames::new();
+        process_names.observe(&LogEntry::test(
+            "am_proc_start",
+            "[0,4375,10123,com.example.foo:remote,service,{}]",
+        ));
+
+        let mut stats = Stats::new().with_process_names(Some(Rc::new(process_names)));
+        for entry in [
+            LogEntry::test("Sync", "started")
+                .with_datetime(utc_datetime!(2025-10-01 12:10:36.1))
+                .with_pid(4375),
+            LogEntry::test("Sync", "syncing")
+                .with_datetime(utc_datetime!(2025-10-01 12:10:36.5))
+                .with_pid(4375),
+            LogEntry::test("ActivityManager", "slow")
+                .with_datetime(utc_datetime!(2025-10-01 12:10:37.0))
+                .with_pid(812)
+                .with_level(LogLevel::Warning),
+            LogEntry::test("ActivityManager", "ANR in com.example.foo")
+                .with_datetime(utc_datetime!(2025-10-01 12:12:01.0))
+                .with_pid(812)
+                .with_level(LogLevel::Error),
+        ] {
+            stats.observe(&entry);
+        }
+        stats.parse_failure();
+        stats
+    }
+
+    #[test]
+    fn test_count_entries() {
+        let stats = stats();
+        assert_eq!(stats.entries, 4);
+        assert_eq!(stats.levels[&LogLevel::Info], 2);
+        assert_eq!(stats.tags["ActivityManager"], 2);
+        assert_eq!(
+            stats.processes(),
+            [(812, None, 2), (4375, Some("com.example.foo:remote".to_owned()), 2)]
+        );
+        assert_eq!(stats.packages(), [("com.example.foo".to_owned(), 2)]);
+        assert_eq!(stats.minutes.values().copied().collect::<Vec<_>>(), [3, 1]);
+        assert_eq!(stats.peak_second, Some((utc_datetime!(2025-10-01 12:10:36), 2)));
+        assert_eq!(stats.crashes[&CrashKind::Anr], 1);
+    }
+
+    #[test]
+    fn test_summarize_as_json() {
+        let summary = serde_json::from_str::<Value>(&stats().to_json()).unwrap();
+        assert_eq!(summary["entries"], 4);
+        assert_eq!(summary["parse_failures"], 1);
+        assert_eq!(summary["levels"]["warning"], 1);
+        assert_eq!(summary["tags"][0], json!({"tag": "ActivityManager", "entries": 2}));
+        assert_eq!(summary["processes"][1]["package"], "com.example.foo");
+        assert_eq!(
+            summary["minutes"][0],
+            json!({"minute": "2025-10-01T12:10:00Z", "entries": 3})
+        );
+        assert_eq!(summary["crashes"]["anr"], 1);
+    }
+
+    #[test]
+    fn test_format_numbers() {
+        assert_eq!(separated(0), "0");
+        assert_eq!(separated(1234), "1,234");
+        assert_eq!(separated(1_234_567), "1,234,567");
+        assert_eq!(format_duration(Duration::seconds(266)), "4m 26s");
+        assert_eq!(format_duration(Duration::seconds(7512)), "2h 5m");
+    }
+}
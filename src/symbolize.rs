@@ -0,0 +1,197 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use anyhow::anyhow;
+
+use crate::prelude::*;
+
+pub const DEFAULT_SYMBOLIZER: &str = "llvm-symbolizer";
+
+/// The function and source location a program counter is in.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Symbol {
+    pub function: String,
+    /// The `file:line` of the program counter, if the library has line tables.
+    pub location: Option<String>,
+}
+
+/// Resolves frames of native backtraces against unstripped libraries, using an external symbolizer that speaks the
+/// protocol of `llvm-symbolizer`: it reads `"library" 0xaddress` lines from stdin, and answers each with the
+/// function and `file:line:column` of every inlined frame, followed by an empty line.
+///
+/// The symbolizer is started when the first frame is resolved, and kept running for the following ones.
+pub struct Symbolizer {
+    command: String,
+    /// Where the unstripped libraries are, either at their path on the device, e.g. `DIR/system/lib64/libc.so`, or
+    /// directly in it, e.g. `DIR/libc.so`.
+    symbols_dir: PathBuf,
+    process: RefCell<Option<SymbolizerProcess>>,
+    /// Set once the symbolizer failed, so that it isn't restarted for every frame.
+    failed: Cell<bool>,
+    cache: RefCell<HashMap<(String, u64), Option<Symbol>>>,
+}
+
+struct SymbolizerProcess {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Symbolizer {
+    pub fn new(command: &str, symbols_dir: &Path) -> Self {
+        Self {
+            command: command.to_owned(),
+            symbols_dir: symbols_dir.to_owned(),
+            process: RefCell::new(None),
+            failed: Cell::new(false),
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the symbol of `pc` in `library`, a path on the device, or `None` if the library isn't in the symbols
+    /// directory or the symbolizer doesn't know the address.
+    pub fn symbolize(&self, library: &str, pc: u64) -> Option<Symbol> {
+        let key = (library.to_owned(), pc);
+        if let Some(symbol) = self.cache.borrow().get(&key) {
+            return symbol.clone();
+        }
+
+        let symbol = self.find_library(library).and_then(|path| {
+            self.query(&path, pc)
+                .inspect_err(|e| {
+                    eprintln!("warning: {e}; native frames will not be symbolized");
+                    self.failed.set(true);
+                    self.process.replace(None);
+                })
+                .ok()
+                .flatten()
+        });
+        self.cache.borrow_mut().insert(key, symbol.clone());
+        symbol
+    }
+
+    fn find_library(&self, library: &str) -> Option<PathBuf> {
+        let on_device = self.symbols_dir.join(library.trim_start_matches('/'));
+        let by_name = Path::new(library)
+            .file_name()
+            .map(|name| self.symbols_dir.join(name));
+        [Some(on_device), by_name]
+            .into_iter()
+            .flatten()
+            .find(|path| path.is_file())
+    }
+
+    fn query(&self, path: &Path, pc: u64) -> Result<Option<Symbol>> {
+        if self.failed.get() {
+            return Ok(None);
+        }
+
+        let mut process = self.process.borrow_mut();
+        if process.is_none() {
+            *process = Some(self.spawn()?);
+        }
+        let SymbolizerProcess { stdin, stdout, .. } = process.as_mut().expect("the symbolizer was just started");
+
+        writeln!(stdin, "\"{}\" {pc:#x}", path.display())
+            .and_then(|()| stdin.flush())
+            .map_err(|e| anyhow!("failed to write to `{}`: {e}", self.command))?;
+
+        let mut answer = Vec::new();
+        loop {
+            let mut line = String::new();
+            let read = stdout
+                .read_line(&mut line)
+                .map_err(|e| anyhow!("failed to read from `{}`: {e}", self.command))?;
+            if read == 0 {
+                return Err(anyhow!("`{}` exited unexpectedly", self.command));
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            answer.push(line.to_owned());
+        }
+
+        Ok(parse_answer(&answer))
+    }
+
+    fn spawn(&self) -> Result<SymbolizerProcess> {
+        let mut child = Command::new(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow!("failed to execute `{}`: {e}", self.command))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        Ok(SymbolizerProcess { child, stdin, stdout })
+    }
+}
+
+impl Drop for SymbolizerProcess {
+    fn drop(&mut self) {
+        // Failing to stop the symbolizer only leaves a process behind, which exits once its stdin is closed anyway.
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Parses the answer of the symbolizer for a single address, using its innermost inlined frame.
+fn parse_answer(answer: &[String]) -> Option<Symbol> {
+    let function = answer.first().filter(|function| *function != "??")?;
+    let location = answer
+        .get(1)
+        .map(|location| trim_column(location))
+        .filter(|location| !location.starts_with("??") && !location.ends_with(":0"))
+        .map(str::to_owned);
+    Some(Symbol {
+        function: function.clone(),
+        location,
+    })
+}
+
+/// Removes the column from a `file:line:column` location.
+fn trim_column(location: &str) -> &str {
+    match location.rsplit_once(':') {
+        Some((file_and_line, column)) if file_and_line.contains(':') && column.parse::<u32>().is_ok() => file_and_line,
+        _ => location,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|&line| line.to_owned()).collect()
+    }
+
+    #[test]
+    fn test_parse_symbolizer_answers() {
+        assert_eq!(
+            parse_answer(&answer(&[
+                "foo::bar(int)",
+                "/src/foo.cpp:42:7",
+                "main",
+                "/src/main.cpp:3:5"
+            ])),
+            Some(Symbol {
+                function: "foo::bar(int)".to_owned(),
+                location: Some("/src/foo.cpp:42".to_owned()),
+            })
+        );
+        assert_eq!(
+            parse_answer(&answer(&["abort", "??:0:0"])),
+            Some(Symbol {
+                function: "abort".to_owned(),
+                location: None,
+            })
+        );
+        assert_eq!(parse_answer(&answer(&["??", "??:0:0"])), None);
+    }
+}
@@ -0,0 +1,462 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use anyhow::anyhow;
+use lazy_regex::regex_captures;
+use strum::{Display, EnumString, IntoEnumIterator};
+use time::{UtcDateTime, format_description::BorrowedFormatItem};
+use time_macros::format_description;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    format::{Style, Theme},
+    log::{LogEntry, LogLevel},
+    prelude::*,
+    zone::Zone,
+};
+
+const SECOND_FORMAT: &[BorrowedFormatItem] = format_description!("[month]-[day] [hour]:[minute]:[second]");
+const MINUTE_FORMAT: &[BorrowedFormatItem] = format_description!("[month]-[day] [hour]:[minute]");
+
+/// The sizes of buckets picked when none is passed, in seconds.
+const BUCKET_SIZES: [i64; 17] = [
+    1, 2, 5, 10, 15, 30, 60, 120, 300, 600, 900, 1800, 3600, 7200, 21600, 43200, 86400,
+];
+
+/// The number of rows of bar charts whose bucket size is picked.
+const MAX_BARS: usize = 40;
+
+/// The blocks of sparklines, from the lowest to the highest.
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The width tag names are cut to in the breakdown of bar charts.
+const MAX_TAG_WIDTH: usize = 16;
+
+/// How the timeline is drawn.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum ChartStyle {
+    /// A row per bucket, with a bar stacked by level.
+    #[default]
+    Bars,
+    /// A line of blocks as high as the volume of each bucket, colored by the most severe level in it.
+    Sparkline,
+}
+
+/// Parses a bucket size such as `30s`, in `s`, `m`, `h` or `d`.
+pub fn parse_bucket(bucket: &str) -> Result<i64> {
+    let invalid = || anyhow!("invalid bucket size `{bucket}`, expected a duration such as `30s`, `5m` or `1h`");
+    let (_, amount, unit) = regex_captures!(r"^(\d+)\s*([smhd])$", bucket.trim()).ok_or_else(invalid)?;
+    let amount = amount.parse::<i64>().map_err(|_| invalid())?;
+    if amount == 0 {
+        return Err(anyhow!("invalid bucket size `{bucket}`, it must be greater than 0"));
+    }
+
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => 24 * 60 * 60,
+    };
+    amount.checked_mul(unit).ok_or_else(invalid)
+}
+
+/// Counts entries per second, by level and tag, to chart their volume over time once the input ends.
+pub struct Timeline {
+    style: ChartStyle,
+    zone: Zone,
+    /// The size of buckets in seconds, or `None` to pick one from the time the entries span.
+    bucket: Option<i64>,
+    /// The number of tags with the most entries charted on their own.
+    top_tags: usize,
+    /// The entries of each second, by level, by the Unix timestamp of the second.
+    seconds: BTreeMap<i64, [usize; LEVELS]>,
+    tags: HashMap<String, BTreeMap<i64, usize>>,
+}
+
+/// The number of levels, to count entries by level in arrays.
+const LEVELS: usize = 7;
+
+/// The counts of the buckets of a chart.
+struct Buckets {
+    /// The Unix timestamp of the first bucket.
+    start: i64,
+    size: i64,
+    levels: Vec<[usize; LEVELS]>,
+    /// The top tags, from the one with the most entries, with the entries of each bucket.
+    tags: Vec<(String, Vec<usize>)>,
+}
+
+impl Timeline {
+    pub fn new(style: ChartStyle, zone: Zone) -> Self {
+        Self {
+            style,
+            zone,
+            bucket: None,
+            top_tags: 0,
+            seconds: BTreeMap::new(),
+            tags: HashMap::new(),
+        }
+    }
+
+    pub const fn with_bucket(mut self, bucket: Option<i64>) -> Self {
+        self.bucket = bucket;
+        self
+    }
+
+    pub const fn with_top_tags(mut self, top_tags: usize) -> Self {
+        self.top_tags = top_tags;
+        self
+    }
+
+    pub fn observe(&mut self, entry: &LogEntry) {
+        let second = entry.datetime.unix_timestamp();
+        self.seconds.entry(second).or_default()[entry.level as usize] += 1;
+        if self.top_tags > 0 {
+            let seconds = self.tags.entry(entry.tag.clone()).or_default();
+            *seconds.entry(second).or_default() += 1;
+        }
+    }
+
+    /// Draws the chart, fitted into `width` columns where possible.
+    pub fn render(&self, theme: &Theme, width: usize) -> String {
+        Chart {
+            timeline: self,
+            theme,
+            width,
+        }
+        .to_string()
+    }
+
+    fn write_chart(&self, f: &mut fmt::Formatter<'_>, theme: &Theme, width: usize) -> fmt::Result {
+        let (Some(&first), Some(&last)) = (self.seconds.keys().next(), self.seconds.keys().next_back()) else {
+            return writeln!(f, "{}", theme.header.apply("No entries to chart"));
+        };
+
+        let label_width = self.time_label(first, 1).width();
+        let bucket = self.bucket.unwrap_or_else(|| {
+            let max_buckets = match self.style {
+                ChartStyle::Bars => MAX_BARS,
+                ChartStyle::Sparkline => width
+                    .saturating_sub(label_width.max(MAX_TAG_WIDTH) + 10)
+                    .max(10),
+            };
+            pick_bucket(last - first + 1, max_buckets)
+        });
+        let buckets = self.buckets(first, last, bucket);
+
+        let peak = buckets.totals().max().unwrap_or_default();
+        writeln!(
+            f,
+            "{}",
+            theme.header.apply(format!(
+                "{} to {}, {} buckets of {}, up to {peak} entries per bucket",
+                self.time_label(first, 1),
+                self.time_label(last, 1),
+                buckets.levels.len(),
+                format_bucket(bucket)
+            ))
+        )?;
+        match self.style {
+            ChartStyle::Bars => self.write_bars(f, &buckets, theme, width),
+            ChartStyle::Sparkline => write_sparklines(f, &buckets, theme),
+        }
+    }
+
+    /// Sums the counts of the seconds from `first` to `last` into buckets of `size` seconds.
+    fn buckets(&self, first: i64, last: i64, size: i64) -> Buckets {
+        let start = first - first.rem_euclid(size);
+        let count = usize::try_from((last - start) / size + 1).unwrap_or_default();
+        let index = |second: i64| usize::try_from((second - start) / size).unwrap_or_default();
+
+        let mut levels = vec![[0; LEVELS]; count];
+        for (&second, counts) in &self.seconds {
+            for (total, count) in levels[index(second)].iter_mut().zip(counts) {
+                *total += count;
+            }
+        }
+
+        let mut tags = self
+            .tags
+            .iter()
+            .map(|(tag, seconds)| (tag, seconds.values().sum::<usize>()))
+            .collect::<Vec<_>>();
+        tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        let tags = tags
+            .into_iter()
+            .take(self.top_tags)
+            .map(|(tag, _)| {
+                let mut counts = vec![0; count];
+                for (&second, &entries) in &self.tags[tag] {
+                    counts[index(second)] += entries;
+                }
+                (tag.clone(), counts)
+            })
+            .collect();
+
+        Buckets {
+            start,
+            size,
+            levels,
+            tags,
+        }
+    }
+
+    /// Draws a row per bucket, with a bar stacked from the most severe level, followed by the entries of the top
+    /// tags.
+    fn write_bars(&self, f: &mut fmt::Formatter<'_>, buckets: &Buckets, theme: &Theme, width: usize) -> fmt::Result {
+        let peak = buckets.totals().max().unwrap_or_default().max(1);
+        let count_width = peak.to_string().len();
+        let tag_names = buckets
+            .tags
+            .iter()
+            .map(|(tag, _)| truncate(tag, MAX_TAG_WIDTH))
+            .collect::<Vec<_>>();
+        let tag_widths = tag_names
+            .iter()
+            .map(|tag| tag.width().max(count_width))
+            .collect::<Vec<_>>();
+        let label_width = self.time_label(buckets.start, buckets.size).width();
+        // Every level with entries is drawn at least one block wide, which takes up to a block per level.
+        let bar_width = width
+            .saturating_sub(label_width + count_width + 3 + LEVELS)
+            .saturating_sub(tag_widths.iter().map(|width| width + 2).sum())
+            .max(10);
+
+        if !tag_names.is_empty() {
+            write!(
+                f,
+                "{:width$}",
+                "",
+                width = label_width + bar_width + LEVELS + count_width + 3
+            )?;
+            for (tag, width) in tag_names.iter().zip(&tag_widths) {
+                write!(f, "  {}", theme.tag.apply(format!("{tag:>width$}")))?;
+            }
+            writeln!(f)?;
+        }
+
+        for (i, levels) in buckets.levels.iter().enumerate() {
+            let second = buckets.start + buckets.size * i64::try_from(i).unwrap_or_default();
+            let label = theme.datetime.apply(self.time_label(second, buckets.size));
+            let mut bar_length = 0;
+            let bar = LogLevel::iter()
+                .rev()
+                .filter(|&level| levels[level as usize] > 0)
+                .map(|level| {
+                    let length = (levels[level as usize] * bar_width).div_ceil(peak);
+                    bar_length += length;
+                    level_style(theme, level)
+                        .apply("█".repeat(length))
+                        .to_string()
+                })
+                .collect::<String>();
+
+            let total = levels.iter().sum::<usize>();
+            let padding = bar_width + LEVELS - bar_length;
+            let tag_counts = buckets
+                .tags
+                .iter()
+                .zip(&tag_widths)
+                .map(|((_, counts), width)| match counts[i] {
+                    0 => format!("  {:width$}", ""),
+                    count => format!("  {count:>width$}"),
+                })
+                .collect::<String>();
+            let row = format!("{label} │{bar}{:padding$} {total:>count_width$}{tag_counts}", "");
+            writeln!(f, "{}", row.trim_end_matches(' '))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the time of the bucket starting at `second`, without seconds when buckets are whole minutes.
+    fn time_label(&self, second: i64, bucket: i64) -> String {
+        let datetime = UtcDateTime::from_unix_timestamp(second).unwrap_or(UtcDateTime::UNIX_EPOCH);
+        let format = if bucket % 60 == 0 { MINUTE_FORMAT } else { SECOND_FORMAT };
+        self.zone
+            .localize(datetime)
+            .format(format)
+            .unwrap_or_else(|e| panic!("failed to format datetime: {e}"))
+    }
+}
+
+/// The chart of a timeline, see [`Timeline::render`].
+struct Chart<'a> {
+    timeline: &'a Timeline,
+    theme: &'a Theme,
+    width: usize,
+}
+
+impl fmt::Display for Chart<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.timeline.write_chart(f, self.theme, self.width)
+    }
+}
+
+impl Buckets {
+    fn totals(&self) -> impl Iterator<Item = usize> {
+        self.levels.iter().map(|levels| levels.iter().sum())
+    }
+}
+
+/// Draws a sparkline of all the entries, colored by the most severe level of each bucket, followed by one per top
+/// tag, scaled to its own peak.
+fn write_sparklines(f: &mut fmt::Formatter<'_>, buckets: &Buckets, theme: &Theme) -> fmt::Result {
+    let tag_names = buckets
+        .tags
+        .iter()
+        .map(|(tag, _)| truncate(tag, MAX_TAG_WIDTH))
+        .collect::<Vec<_>>();
+    let name_width = tag_names
+        .iter()
+        .map(|tag| tag.width())
+        .max()
+        .unwrap_or_default()
+        .max(3);
+
+    let totals = buckets.totals().collect::<Vec<_>>();
+    let peak = totals.iter().copied().max().unwrap_or_default();
+    write!(f, "{:name_width$} ", "all")?;
+    for (&total, levels) in totals.iter().zip(&buckets.levels) {
+        let severest = LogLevel::iter()
+            .rev()
+            .find(|&level| levels[level as usize] > 0);
+        let block = block(total, peak).to_string();
+        match severest {
+            Some(level) => write!(f, "{}", level_style(theme, level).apply(block)),
+            None => write!(f, "{block}"),
+        }?;
+    }
+    writeln!(f, " {}", totals.iter().sum::<usize>())?;
+
+    for ((_, counts), name) in buckets.tags.iter().zip(tag_names) {
+        let peak = counts.iter().copied().max().unwrap_or_default();
+        let sparkline = counts
+            .iter()
+            .map(|&count| block(count, peak))
+            .collect::<String>();
+        writeln!(
+            f,
+            "{} {sparkline} {}",
+            theme.tag.apply(format!("{name:name_width$}")),
+            counts.iter().sum::<usize>()
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the block of a sparkline for `count` out of `peak`, or a space for no entries.
+fn block(count: usize, peak: usize) -> char {
+    if count == 0 {
+        return ' ';
+    }
+    BLOCKS[((count * BLOCKS.len()).div_ceil(peak.max(1)) - 1).min(BLOCKS.len() - 1)]
+}
+
+/// Returns the style of the blocks of a level, the background of its badge, e.g. red for errors.
+fn level_style(theme: &Theme, level: LogLevel) -> Style {
+    theme
+        .level(level)
+        .badge
+        .background
+        .map_or_else(Style::plain, Style::fg)
+}
+
+/// Returns the smallest bucket size for the seconds of `span` to fit into `max_buckets`.
+fn pick_bucket(span: i64, max_buckets: usize) -> i64 {
+    let max_buckets = i64::try_from(max_buckets).unwrap_or(i64::MAX).max(1);
+    let buckets = |size: i64| (span + size - 1) / size;
+    BUCKET_SIZES
+        .into_iter()
+        .find(|&size| buckets(size) <= max_buckets)
+        .unwrap_or_else(|| buckets(max_buckets))
+}
+
+/// Formats a bucket size in its largest whole unit, e.g. `90s` or `5m`.
+fn format_bucket(seconds: i64) -> String {
+    [(86400, "d"), (3600, "h"), (60, "m")]
+        .into_iter()
+        .find(|(unit, _)| seconds % unit == 0)
+        .map_or_else(
+            || format!("{seconds}s"),
+            |(unit, name)| format!("{}{name}", seconds / unit),
+        )
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_owned();
+    }
+    let mut truncated = String::new();
+    for c in text.chars() {
+        if truncated.width() + c.to_string().width() >= width {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+    use time_macros::utc_datetime;
+
+    use super::*;
+
+    fn timeline(style: ChartStyle) -> Timeline {
+        let mut timeline = Timeline::new(style, Zone::Utc)
+            .with_bucket(Some(60))
+            .with_top_tags(1);
+        let start = utc_datetime!(2025-10-01 12:10:36);
+        for (seconds, level, tag) in [
+            (0, LogLevel::Info, "Finsky"),
+            (1, LogLevel::Error, "Finsky"),
+            (2, LogLevel::Warning, "ActivityManager"),
+            (150, LogLevel::Info, "Finsky"),
+        ] {
+            timeline.observe(
+                &LogEntry::test(tag, "message")
+                    .with_datetime(start)
+                    .after(Duration::seconds(seconds))
+                    .with_level(level),
+            );
+        }
+        timeline
+    }
+
+    #[test]
+    fn test_parse_bucket() {
+        assert_eq!(parse_bucket("30s").unwrap(), 30);
+        assert_eq!(parse_bucket("5m").unwrap(), 300);
+        assert_eq!(parse_bucket("1d").unwrap(), 86400);
+        assert!(parse_bucket("0s").is_err());
+        assert!(parse_bucket("5M").is_err());
+        assert_eq!(pick_bucket(3 * 60 * 60, 40), 300);
+        assert_eq!(format_bucket(90), "90s");
+    }
+
+    #[test]
+    fn test_render_bars() {
+        let chart = timeline(ChartStyle::Bars).render(&Theme::default(), 40);
+        assert_eq!(
+            chart,
+            "10-01 12:10:36 to 10-01 12:13:06, 4 buckets of 1m, up to 3 entries per bucket
+                                  Finsky
+10-01 12:10 │████████████      3       2
+10-01 12:11 │                  0
+10-01 12:12 │                  0
+10-01 12:13 │████              1       1
+"
+        );
+    }
+
+    #[test]
+    fn test_render_sparklines() {
+        let chart = timeline(ChartStyle::Sparkline).render(&Theme::default(), 80);
+        assert!(chart.ends_with("all    █  ▃ 4\nFinsky █  ▄ 3\n"));
+    }
+}
@@ -0,0 +1,141 @@
+use lazy_regex::regex_captures;
+
+use crate::log::LogEntry;
+
+/// The tags native crashes are logged under: `libc` for the fatal signal, and `DEBUG` for the tombstone.
+const TOMBSTONE_TAGS: [&str; 2] = ["DEBUG", "libc"];
+
+/// A line of a native crash that is formatted differently than other lines.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TombstoneLine<'a> {
+    /// The name of the signal of `Fatal signal 11 (SIGSEGV), code 1 ...` or `signal 11 (SIGSEGV), code 1 ...`.
+    Signal(&'a str),
+    /// `Abort message: '...'`.
+    AbortMessage(&'a str),
+    Frame(NativeFrame<'a>),
+}
+
+impl<'a> TombstoneLine<'a> {
+    pub fn parse(entry: &'a LogEntry) -> Option<Self> {
+        if !TOMBSTONE_TAGS.contains(&entry.tag.as_str()) {
+            return None;
+        }
+
+        let message = entry.message.as_str();
+        if let Some(frame) = NativeFrame::parse(message) {
+            return Some(Self::Frame(frame));
+        }
+        if let Some((_, name)) = regex_captures!(r"^(?:Fatal )?signal \d+ \((\w+)\)", message) {
+            return Some(Self::Signal(name));
+        }
+        regex_captures!(r"^Abort message: '(.*)'$", message).map(|(_, abort_message)| Self::AbortMessage(abort_message))
+    }
+}
+
+/// A frame of the backtrace of a native crash, e.g.
+/// `#00 pc 000000000004f1e4  /system/lib64/libc.so (abort+164) (BuildId: 5e3b3c1a)`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct NativeFrame<'a> {
+    pub index: &'a str,
+    /// The program counter, relative to the start of the library, in hex.
+    pub pc: &'a str,
+    pub library: &'a str,
+    /// The offset of the library in the file it's loaded from, e.g. when loaded directly from an APK.
+    pub file_offset: Option<&'a str>,
+    /// The function and the offset of the program counter in it, e.g. `abort+164`.
+    pub function: Option<&'a str>,
+    pub build_id: Option<&'a str>,
+}
+
+impl<'a> NativeFrame<'a> {
+    fn parse(message: &'a str) -> Option<Self> {
+        let (_, index, pc, library, file_offset, function, build_id) = regex_captures!(
+            r"^\s*#(\d+) pc ([0-9a-f]+)\s+(\S+)(?: \(offset (0x[0-9a-f]+)\))?(?: \((.+?)\))?(?: \(BuildId: ([0-9a-f]+)\))?\s*$",
+            message
+        )?;
+        let optional = |capture: &'a str| (!capture.is_empty()).then_some(capture);
+        // Without a function, the build id is the only parenthesized part after the library.
+        let (function, build_id) = match function.strip_prefix("BuildId: ") {
+            Some(only_build_id) if build_id.is_empty() => (None, Some(only_build_id)),
+            _ => (optional(function), optional(build_id)),
+        };
+        Some(Self {
+            index,
+            pc,
+            library,
+            file_offset: optional(file_offset),
+            function,
+            build_id,
+        })
+    }
+
+    pub fn pc_value(&self) -> Option<u64> {
+        u64::from_str_radix(self.pc, 16).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tombstone_lines() {
+        let signal = LogEntry::test(
+            "libc",
+            "Fatal signal 11 (SIGSEGV), code 1 (SEGV_MAPERR), fault addr 0x0 in tid 8140 (RenderThread), pid 8123",
+        );
+        assert_eq!(TombstoneLine::parse(&signal), Some(TombstoneLine::Signal("SIGSEGV")));
+
+        let signal = LogEntry::test("DEBUG", "signal 6 (SIGABRT), code -1 (SI_QUEUE), fault addr --------");
+        assert_eq!(TombstoneLine::parse(&signal), Some(TombstoneLine::Signal("SIGABRT")));
+
+        let abort = LogEntry::test("DEBUG", "Abort message: 'Check failed: index < size'");
+        assert_eq!(
+            TombstoneLine::parse(&abort),
+            Some(TombstoneLine::AbortMessage("Check failed: index < size"))
+        );
+
+        let frame = LogEntry::test(
+            "DEBUG",
+            "      #00 pc 000000000004f1e4  /system/lib64/libc.so (abort+164) (BuildId: 5e3b3c1a)",
+        );
+        assert_eq!(
+            TombstoneLine::parse(&frame),
+            Some(TombstoneLine::Frame(NativeFrame {
+                index: "00",
+                pc: "000000000004f1e4",
+                library: "/system/lib64/libc.so",
+                file_offset: None,
+                function: Some("abort+164"),
+                build_id: Some("5e3b3c1a"),
+            }))
+        );
+
+        let frame = LogEntry::test(
+            "DEBUG",
+            "      #03 pc 00000000000a1234  /data/app/com.example-1/base.apk (offset 0x1000) (foo::bar(int)+32)",
+        );
+        let Some(TombstoneLine::Frame(frame)) = TombstoneLine::parse(&frame) else {
+            panic!("Expected a native frame");
+        };
+        assert_eq!(frame.file_offset, Some("0x1000"));
+        assert_eq!(frame.function, Some("foo::bar(int)+32"));
+        assert_eq!(frame.pc_value(), Some(0xa1234));
+
+        let frame = LogEntry::test(
+            "DEBUG",
+            "      #01 pc 000000000000110d  /system/lib64/libfoo.so (BuildId: 77aa)",
+        );
+        let Some(TombstoneLine::Frame(frame)) = TombstoneLine::parse(&frame) else {
+            panic!("Expected a native frame");
+        };
+        assert_eq!(frame.function, None);
+        assert_eq!(frame.build_id, Some("77aa"));
+
+        assert_eq!(
+            TombstoneLine::parse(&LogEntry::test("Finsky", "signal 11 (SIGSEGV)")),
+            None
+        );
+        assert_eq!(TombstoneLine::parse(&LogEntry::test("DEBUG", "backtrace:")), None);
+    }
+}
@@ -0,0 +1,230 @@
+use std::{env, str::FromStr};
+
+use anyhow::anyhow;
+use lazy_regex::regex_captures;
+use time::{
+    Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcDateTime, UtcOffset,
+    format_description::well_known::Rfc3339,
+};
+use time_tz::{OffsetDateTimeExt, PrimitiveDateTimeExt, Tz, system, timezones};
+
+use crate::prelude::*;
+
+/// The time zone timestamps are logged or displayed in.
+#[derive(Debug, Clone, Copy)]
+pub enum Zone {
+    Utc,
+    /// A fixed offset from UTC, e.g. printed by the `zone` modifier of logcat.
+    Fixed(UtcOffset),
+    Named(&'static Tz),
+}
+
+impl Zone {
+    /// Returns the time zone of this machine: `TZ` if it names a time zone, or the zone `/etc/localtime` links to.
+    pub fn local() -> Result<Self> {
+        if let Some(tz) = env::var("TZ")
+            .ok()
+            .and_then(|tz| timezones::get_by_name(tz.trim_start_matches(':')))
+        {
+            return Ok(Self::Named(tz));
+        }
+
+        system::get_timezone()
+            .map(Self::Named)
+            .map_err(|e| anyhow!("failed to determine the local time zone: {e}"))
+    }
+
+    /// Returns the time zone set by the `persist.sys.timezone` property of a bugreport, if `line` sets it.
+    pub fn from_bugreport_property(line: &str) -> Option<Self> {
+        let (_, name) = regex_captures!(r"^\[persist\.sys\.timezone\]: \[([^\]]+)\]$", line.trim_end())?;
+        timezones::get_by_name(name).map(Self::Named)
+    }
+
+    /// Converts a time on the clocks of this zone to UTC. Times skipped or repeated by daylight saving time changes
+    /// are converted with the offset in effect before the change.
+    pub fn to_utc(self, datetime: PrimitiveDateTime) -> UtcDateTime {
+        let offset = match self {
+            Self::Utc => UtcOffset::UTC,
+            Self::Fixed(offset) => offset,
+            // Daylight saving time changes skip at most an hour, so an hour earlier is before the change.
+            Self::Named(tz) => datetime
+                .assume_timezone(tz)
+                .take_first()
+                .or_else(|| (datetime - Duration::HOUR).assume_timezone(tz).take_first())
+                .map_or(UtcOffset::UTC, OffsetDateTime::offset),
+        };
+        datetime.assume_offset(offset).to_utc()
+    }
+
+    /// Parses a time on the clocks of this zone, as `2025-10-01 12:10[:36[.746]]`, or `10-01 12:10[:36[.746]]` like
+    /// logcat, in `year`. Times in RFC 3339 are parsed with their own offset.
+    pub fn parse_datetime(self, datetime: &str, year: i32) -> Result<UtcDateTime> {
+        if let Ok(datetime) = OffsetDateTime::parse(datetime, &Rfc3339) {
+            return Ok(datetime.to_utc());
+        }
+
+        let invalid =
+            || anyhow!("invalid time `{datetime}`, expected a time such as `10-01 12:10:36` or `2025-10-01 12:10`");
+        let (_, year_digits, month, day, hour, minute, second, fraction) = regex_captures!(
+            r"^(?:(\d{4})-)?(\d{2})-(\d{2})[ T](\d{2}):(\d{2})(?::(\d{2})(?:\.(\d{1,9}))?)?$",
+            datetime.trim()
+        )
+        .ok_or_else(invalid)?;
+        // Fields left out are 0, and the fraction of seconds is padded to nanoseconds.
+        let field = |digits: &str| {
+            if digits.is_empty() {
+                Ok(0)
+            } else {
+                digits.parse::<u8>().map_err(|_| invalid())
+            }
+        };
+        let year = if year_digits.is_empty() {
+            year
+        } else {
+            year_digits.parse().map_err(|_| invalid())?
+        };
+        let nanosecond = format!("{fraction:0<9}")
+            .parse::<u32>()
+            .map_err(|_| invalid())?;
+
+        let (month, day) = (field(month)?, field(day)?);
+        let date = Month::try_from(month)
+            .and_then(|month| Date::from_calendar_date(year, month, day))
+            .map_err(|_| invalid())?;
+        let time =
+            Time::from_hms_nano(field(hour)?, field(minute)?, field(second)?, nanosecond).map_err(|_| invalid())?;
+        Ok(self.to_utc(PrimitiveDateTime::new(date, time)))
+    }
+
+    /// Converts a UTC time to the clocks of this zone.
+    pub fn localize(self, datetime: UtcDateTime) -> OffsetDateTime {
+        match self {
+            Self::Utc => datetime.to_offset(UtcOffset::UTC),
+            Self::Fixed(offset) => datetime.to_offset(offset),
+            Self::Named(tz) => datetime.to_offset(UtcOffset::UTC).to_timezone(tz),
+        }
+    }
+}
+
+impl FromStr for Zone {
+    type Err = anyhow::Error;
+
+    /// Parses `utc`, `local`, an offset such as `+0200` or `-07:00`, or an IANA time zone name such as
+    /// `Europe/Berlin`.
+    fn from_str(zone: &str) -> Result<Self> {
+        if zone.eq_ignore_ascii_case("utc") {
+            return Ok(Self::Utc);
+        }
+        if zone.eq_ignore_ascii_case("local") {
+            return Self::local();
+        }
+        if let Some(offset) = parse_offset(zone) {
+            return Ok(Self::Fixed(offset));
+        }
+
+        timezones::get_by_name(zone)
+            .map(Self::Named)
+            .ok_or_else(|| anyhow!("unknown time zone `{zone}`, expected `local`, `utc`, an offset or an IANA name"))
+    }
+}
+
+/// Parses an offset from UTC in the form `+HHMM` or `+HH:MM`.
+pub fn parse_offset(offset: &str) -> Option<UtcOffset> {
+    let (_, sign, hours, minutes) = regex_captures!(r"^([+-])(\d{2}):?(\d{2})$", offset)?;
+    let sign = if sign == "-" { -1 } else { 1 };
+    UtcOffset::from_hms(sign * hours.parse::<i8>().ok()?, sign * minutes.parse::<i8>().ok()?, 0).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use time_macros::{datetime, utc_datetime};
+    use time_tz::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_zones() {
+        assert!(matches!("UTC".parse::<Zone>().unwrap(), Zone::Utc));
+        assert!(matches!(
+            "-07:00".parse::<Zone>().unwrap(),
+            Zone::Fixed(offset) if offset.whole_hours() == -7
+        ));
+        assert!(matches!("Europe/Berlin".parse::<Zone>().unwrap(), Zone::Named(_)));
+        assert!("Mars/Olympus_Mons".parse::<Zone>().is_err());
+        assert!("+02:60".parse::<Zone>().is_err());
+    }
+
+    #[test]
+    fn test_convert_between_zones() {
+        let berlin = "Europe/Berlin".parse::<Zone>().unwrap();
+
+        assert_eq!(
+            berlin.to_utc(datetime!(2025-10-01 14:10:36.746)),
+            utc_datetime!(2025-10-01 12:10:36.746)
+        );
+        assert_eq!(
+            berlin.to_utc(datetime!(2025-01-01 14:10:36.746)),
+            utc_datetime!(2025-01-01 13:10:36.746)
+        );
+        assert_eq!(
+            Zone::Fixed(parse_offset("-0700").unwrap()).to_utc(datetime!(2025-10-01 05:10:36.746)),
+            utc_datetime!(2025-10-01 12:10:36.746)
+        );
+
+        let displayed = berlin.localize(utc_datetime!(2025-10-01 12:10:36.746));
+        assert_eq!(
+            PrimitiveDateTime::new(displayed.date(), displayed.time()),
+            datetime!(2025-10-01 14:10:36.746)
+        );
+        assert_eq!(displayed.offset().whole_hours(), 2);
+    }
+
+    #[test]
+    fn test_convert_across_daylight_saving_time_changes() {
+        let berlin = "Europe/Berlin".parse::<Zone>().unwrap();
+
+        // Clocks jumped from 02:00 to 03:00, so 02:30 was never displayed.
+        assert_eq!(
+            berlin.to_utc(datetime!(2025-03-30 02:30)),
+            utc_datetime!(2025-03-30 01:30)
+        );
+        // Clocks went back from 03:00 to 02:00, so 02:30 was displayed twice.
+        assert_eq!(
+            berlin.to_utc(datetime!(2025-10-26 02:30)),
+            utc_datetime!(2025-10-26 00:30)
+        );
+    }
+
+    #[test]
+    fn test_parse_datetimes_in_zone() {
+        let berlin = "Europe/Berlin".parse::<Zone>().unwrap();
+
+        assert_eq!(
+            berlin.parse_datetime("10-01 14:10", 2025).unwrap(),
+            utc_datetime!(2025-10-01 12:10)
+        );
+        assert_eq!(
+            berlin
+                .parse_datetime("2024-01-01 14:10:36.5", 2025)
+                .unwrap(),
+            utc_datetime!(2024-01-01 13:10:36.5)
+        );
+        assert_eq!(
+            berlin
+                .parse_datetime("2025-10-01T12:10:36-07:00", 2025)
+                .unwrap(),
+            utc_datetime!(2025-10-01 19:10:36)
+        );
+        assert!(berlin.parse_datetime("13-01 12:10", 2025).is_err());
+        assert!(berlin.parse_datetime("12:10", 2025).is_err());
+    }
+
+    #[test]
+    fn test_bugreport_time_zone_property() {
+        assert!(matches!(
+            Zone::from_bugreport_property("[persist.sys.timezone]: [Europe/Berlin]"),
+            Some(Zone::Named(tz)) if tz.name() == "Europe/Berlin"
+        ));
+        assert!(Zone::from_bugreport_property("[persist.sys.locale]: [en-US]").is_none());
+    }
+}